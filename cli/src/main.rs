@@ -28,6 +28,13 @@ async fn main() -> Result<()> {
         .help("specifies the keypair to use for signing transactions")
         .required(false)
     )
+    .arg(
+        Arg::with_name("force")
+        .long("force")
+        .help("file proposals even when simulating their instruction fails")
+        .global(true)
+        .takes_value(false)
+    )
     .subcommand(
         SubCommand::with_name("config")
         .about("configuration management commands")
@@ -141,15 +148,30 @@ async fn main() -> Result<()> {
                 .help("the source token account to transfer from")
                 .takes_value(true)
                 .value_name("ADDRESS")
+                .required_unless("token")
+            )
+            .arg(
+                Arg::with_name("token")
+                .long("token")
+                .help("the name of a configured token account to transfer from")
+                .takes_value(true)
+                .value_name("NAME")
+                .conflicts_with("source")
             )
             .arg(
                 Arg::with_name("target")
                 .short("t")
                 .long("target")
-                .help("the target to transfer tokens to (must be a token account)")
+                .help("the target to transfer tokens to, either a token account or a wallet whose ata is used")
                 .takes_value(true)
                 .value_name("ADDRESS")
             )
+            .arg(
+                Arg::with_name("create-recipient-ata")
+                .long("create-recipient-ata")
+                .help("create the target wallet's ata alongside the proposal if it does not exist")
+                .takes_value(false)
+            )
             .arg(
                 Arg::with_name("amount")
                 .short("a")
@@ -157,14 +179,6 @@ async fn main() -> Result<()> {
                 .help("the amount of tokens to send, denominated in 'ui amount'")
                 .takes_value(true)
                 .value_name("AMOUNT")
-            )
-            .arg(
-                Arg::with_name("decimals")
-                .short("d")
-                .long("decimals")
-                .help("the number of decimals in the token, used for converting ui amounts to token amounts")
-                .takes_value(true)
-                .value_name("DECIMALS")
             ),
             SubCommand::with_name("submit-base64-bincode-ix")
             .about("submits a base64 bincode serialized instruction")
@@ -181,6 +195,424 @@ async fn main() -> Result<()> {
                 .long("name")
                 .help("the name of the multisig we are submitting to")
                 .takes_value(true)
+            ),
+            SubCommand::with_name("approve")
+            .about("approve a pending multisig transaction")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig the transaction belongs to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("tx")
+                .long("tx")
+                .help("the address of the transaction account to approve")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            ),
+            SubCommand::with_name("execute")
+            .about("execute a multisig transaction that has reached the approval threshold")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig the transaction belongs to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("tx")
+                .long("tx")
+                .help("the address of the transaction account to execute")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            ),
+            SubCommand::with_name("list-proposals")
+            .about("list the transactions proposed to a multisig")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig to list proposals for")
+                .takes_value(true)
+            ),
+            SubCommand::with_name("show-proposal")
+            .about("decode and display the instruction stored in a proposal")
+            .arg(
+                Arg::with_name("tx")
+                .long("tx")
+                .help("the address of the transaction account to show")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            ),
+            SubCommand::with_name("propose-set-owners")
+            .about("propose replacing the owners of the multisig")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("owners")
+                .short("o")
+                .long("owners")
+                .help("comma separated list of the new owners")
+                .takes_value(true)
+            ),
+            SubCommand::with_name("propose-change-threshold")
+            .about("propose changing the approval threshold of the multisig")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("threshold")
+                .short("t")
+                .long("threshold")
+                .help("the new minimum required signers")
+                .takes_value(true)
+                .value_name("COUNT")
+            ),
+            SubCommand::with_name("propose-set-owners-and-threshold")
+            .about("propose replacing the owners and approval threshold of the multisig")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("owners")
+                .short("o")
+                .long("owners")
+                .help("comma separated list of the new owners")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("threshold")
+                .short("t")
+                .long("threshold")
+                .help("the new minimum required signers")
+                .takes_value(true)
+                .value_name("COUNT")
+            ),
+            SubCommand::with_name("sync")
+            .about("update multisig config entries from their on-chain state")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig to sync")
+                .takes_value(true)
+                .required_unless("all")
+            )
+            .arg(
+                Arg::with_name("all")
+                .long("all")
+                .help("sync every multisig in the config file")
+                .takes_value(false)
+                .conflicts_with("name")
+            ),
+            SubCommand::with_name("import")
+            .about("import an existing on-chain multisig into the config file")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("used to name the multisig account in the config file")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("account")
+                .short("a")
+                .long("account")
+                .help("the address of the on-chain multisig account")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            ),
+            SubCommand::with_name("propose-upgrade")
+            .about("propose upgrading a program from a buffer owned by the multisig")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("program-id")
+                .long("program-id")
+                .help("the program to upgrade, by address or adopted program name")
+                .takes_value(true)
+                .value_name("PROGRAM")
+            )
+            .arg(
+                Arg::with_name("buffer")
+                .long("buffer")
+                .help("the buffer holding the new program data")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            )
+            .arg(
+                Arg::with_name("spill")
+                .long("spill")
+                .help("the account receiving the buffer lamports, defaults to the signer")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            ),
+            SubCommand::with_name("deploy-upgrade")
+            .about("write a program to a buffer, hand the buffer to the multisig and propose the upgrade")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("program-id")
+                .long("program-id")
+                .help("the program to upgrade, by address or adopted program name")
+                .takes_value(true)
+                .value_name("PROGRAM")
+            )
+            .arg(
+                Arg::with_name("so")
+                .long("so")
+                .help("path to the compiled program, the buffer keypair is kept next to it until the proposal is filed")
+                .takes_value(true)
+                .value_name("FILE")
+            ),
+            SubCommand::with_name("verify-upgrade")
+            .about("verify that the buffer of an upgrade proposal matches a local build")
+            .arg(
+                Arg::with_name("tx")
+                .long("tx")
+                .help("the address of the upgrade proposal")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            )
+            .arg(
+                Arg::with_name("so")
+                .long("so")
+                .help("path to the locally built program")
+                .takes_value(true)
+                .value_name("FILE")
+            )
+            .arg(
+                Arg::with_name("check-deployed")
+                .long("check-deployed")
+                .help("also compare against the currently deployed program data")
+                .takes_value(false)
+            ),
+            SubCommand::with_name("propose-set-upgrade-authority")
+            .about("propose changing or revoking the upgrade authority of a program owned by the multisig")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("program-id")
+                .long("program-id")
+                .help("the program whose upgrade authority is changed, by address or adopted program name")
+                .takes_value(true)
+                .value_name("PROGRAM")
+            )
+            .arg(
+                Arg::with_name("new-authority")
+                .long("new-authority")
+                .help("the new upgrade authority")
+                .takes_value(true)
+                .value_name("ADDRESS")
+                .required_unless("final")
+            )
+            .arg(
+                Arg::with_name("final")
+                .long("final")
+                .help("revoke the upgrade authority, making the program immutable")
+                .takes_value(false)
+                .conflicts_with("new-authority")
+            ),
+            SubCommand::with_name("adopt-program")
+            .about("transfer the upgrade authority of a program to the multisig pda")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig adopting the program")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("program-id")
+                .long("program-id")
+                .help("the program to adopt, the signer must be its current upgrade authority")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            )
+            .arg(
+                Arg::with_name("program-name")
+                .long("program-name")
+                .help("used to name the program in the config file")
+                .takes_value(true)
+            ),
+            SubCommand::with_name("transfer-sol")
+            .about("submit a SOL transfer from the multisig pda as a proposal")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("target")
+                .short("t")
+                .long("target")
+                .help("the account to transfer SOL to")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            )
+            .arg(
+                Arg::with_name("amount")
+                .short("a")
+                .long("amount")
+                .help("the amount of SOL to send")
+                .takes_value(true)
+                .value_name("AMOUNT")
+            ),
+            SubCommand::with_name("balances")
+            .about("report the SOL and token balances held by multisig pdas")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig to report on, defaults to every configured multisig")
+                .takes_value(true)
+            ),
+            SubCommand::with_name("batch-transfer")
+            .about("submit one token transfer proposal per row of a csv file")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .help("csv file with recipient, token (configured name or mint) and amount columns")
+                .takes_value(true)
+                .value_name("FILE")
+            )
+            .arg(
+                Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("where to write the results csv, defaults to <file>.results.csv")
+                .takes_value(true)
+                .value_name("FILE")
+            ),
+            SubCommand::with_name("approve-all")
+            .about("review and approve every pending proposal of a multisig")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig to approve proposals for")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("filter")
+                .long("filter")
+                .help("only consider proposals matching the filter, eg program=<id>")
+                .takes_value(true)
+                .value_name("FILTER")
+            )
+            .arg(
+                Arg::with_name("yes")
+                .short("y")
+                .long("yes")
+                .help("approve every matching proposal without prompting")
+                .takes_value(false)
+            ),
+            SubCommand::with_name("execute-ready")
+            .about("simulate and execute every proposal that has reached the approval threshold")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig to execute proposals for")
+                .takes_value(true)
+            ),
+            SubCommand::with_name("simulate")
+            .about("simulate executing a proposal and show the resulting balance changes")
+            .arg(
+                Arg::with_name("tx")
+                .long("tx")
+                .help("the address of the proposal to simulate")
+                .takes_value(true)
+                .value_name("ADDRESS")
+            ),
+            SubCommand::with_name("submit-ix")
+            .about("submits an instruction read from an argument, a file or stdin")
+            .arg(
+                Arg::with_name("name")
+                .short("n")
+                .long("name")
+                .help("the name of the multisig we are submitting to")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("data")
+                .long("data")
+                .help("the serialized instruction, read from stdin when neither --data nor --file is given")
+                .takes_value(true)
+                .value_name("DATA")
+                .conflicts_with("file")
+            )
+            .arg(
+                Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .help("a file containing the serialized instruction")
+                .takes_value(true)
+                .value_name("FILE")
+            )
+            .arg(
+                Arg::with_name("format")
+                .long("format")
+                .help("how the instruction is serialized")
+                .takes_value(true)
+                .possible_values(&["bincode", "json", "message", "transaction"])
+                .default_value("bincode")
+            )
+            .arg(
+                Arg::with_name("encoding")
+                .long("encoding")
+                .help("the text encoding of bincode, message and transaction input")
+                .takes_value(true)
+                .possible_values(&["base64", "base58"])
+                .default_value("base64")
+            )
+            .arg(
+                Arg::with_name("index")
+                .long("index")
+                .help("the instruction to propose from a message or transaction")
+                .takes_value(true)
+                .value_name("INDEX")
+                .default_value("0")
             )
         ])
     )
@@ -238,6 +670,71 @@ async fn process_matches<'a>(
             ("submit-base64-bincode-ix", Some(submit)) => {
                 multisig::submit_base64_bincode_ix(submit, config_file_path, keypair)
             }
+            ("approve", Some(approve)) => {
+                multisig::approve(approve, config_file_path, keypair)
+            }
+            ("execute", Some(execute)) => {
+                multisig::execute(execute, config_file_path, keypair)
+            }
+            ("list-proposals", Some(list_proposals)) => {
+                multisig::list_proposals(list_proposals, config_file_path)
+            }
+            ("show-proposal", Some(show_proposal)) => {
+                multisig::show_proposal(show_proposal, config_file_path)
+            }
+            ("propose-set-owners", Some(set_owners)) => {
+                multisig::propose_set_owners(set_owners, config_file_path, keypair)
+            }
+            ("propose-change-threshold", Some(change_threshold)) => {
+                multisig::propose_change_threshold(change_threshold, config_file_path, keypair)
+            }
+            ("propose-set-owners-and-threshold", Some(set_owners_and_threshold)) => {
+                multisig::propose_set_owners_and_threshold(
+                    set_owners_and_threshold,
+                    config_file_path,
+                    keypair,
+                )
+            }
+            ("sync", Some(sync)) => multisig::sync(sync, config_file_path),
+            ("import", Some(import)) => multisig::import(import, config_file_path),
+            ("propose-upgrade", Some(propose_upgrade)) => {
+                multisig::propose_upgrade(propose_upgrade, config_file_path, keypair)
+            }
+            ("deploy-upgrade", Some(deploy_upgrade)) => {
+                multisig::deploy_upgrade(deploy_upgrade, config_file_path, keypair)
+            }
+            ("verify-upgrade", Some(verify_upgrade)) => {
+                multisig::verify_upgrade(verify_upgrade, config_file_path)
+            }
+            ("propose-set-upgrade-authority", Some(set_upgrade_authority)) => {
+                multisig::propose_set_upgrade_authority(
+                    set_upgrade_authority,
+                    config_file_path,
+                    keypair,
+                )
+            }
+            ("adopt-program", Some(adopt_program)) => {
+                multisig::adopt_program(adopt_program, config_file_path, keypair)
+            }
+            ("transfer-sol", Some(transfer_sol)) => {
+                multisig::transfer_sol(transfer_sol, config_file_path, keypair)
+            }
+            ("balances", Some(balances)) => multisig::balances(balances, config_file_path),
+            ("batch-transfer", Some(batch_transfer)) => {
+                multisig::batch_transfer(batch_transfer, config_file_path, keypair)
+            }
+            ("approve-all", Some(approve_all)) => {
+                multisig::approve_all(approve_all, config_file_path, keypair)
+            }
+            ("execute-ready", Some(execute_ready)) => {
+                multisig::execute_ready(execute_ready, config_file_path, keypair)
+            }
+            ("simulate", Some(simulate)) => {
+                multisig::simulate(simulate, config_file_path, keypair)
+            }
+            ("submit-ix", Some(submit)) => {
+                multisig::submit_ix(submit, config_file_path, keypair)
+            }
             _ => invalid_subcommand("multisig"),
         },
         _ => invalid_command(),
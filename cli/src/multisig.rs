@@ -1,9 +1,13 @@
 use anchor_client::{
     anchor_lang::AccountDeserialize,
-    solana_client::rpc_client::RpcClient,
+    solana_client::{rpc_client::RpcClient, rpc_request::TokenAccountsFilter},
     solana_sdk::{
+        account::Account,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        hash::hash,
+        loader_upgradeable_instruction::UpgradeableLoaderInstruction,
         pubkey::Pubkey,
-        signature::{Keypair, Signer},
+        signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
         system_instruction, sysvar,
         transaction::Transaction,
     },
@@ -13,10 +17,16 @@ use anyhow::{anyhow, Result};
 use config::{Configuration, MultiSigAccount};
 use rand::rngs::OsRng;
 use serum_multisig::Multisig;
+use solana_account_decoder::UiAccountData;
+use spl_token::solana_program::program_pack::Pack;
 use solana_clap_utils::keypair::signer_from_path;
 use solana_clap_utils::keypair::DefaultSigner;
 use solana_remote_wallet::remote_wallet;
+use std::fs;
+use std::io;
+use std::io::Read;
 use std::mem;
+use std::path::Path;
 use std::str::FromStr;
 
 pub fn new_multisig_config(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
@@ -32,6 +42,7 @@ pub fn new_multisig_config(matches: &clap::ArgMatches, config_file_path: String)
         pda_nonce: 0,
         owners,
         token_accounts: vec![],
+        programs: vec![],
     });
     config.save(&config_file_path, false)?;
     Ok(())
@@ -130,11 +141,14 @@ pub fn transfer_tokens(
     println!("signer {}", signer.pubkey());
     let multisig_name = matches.value_of("name").unwrap();
     let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
-    let source = Pubkey::from_str(matches.value_of("source").unwrap()).unwrap();
-    let target = Pubkey::from_str(matches.value_of("target").unwrap()).unwrap();
-    let amount = f64::from_str(matches.value_of("amount").unwrap()).unwrap();
-    let decimals = u8::from_str(matches.value_of("decimals").unwrap()).unwrap();
-    let amount = spl_token::ui_amount_to_amount(amount, decimals);
+    let source = match matches.value_of("token") {
+        Some(token) => match multisig_config.token_account_by_name(token) {
+            Some(source) => source,
+            None => return Err(anyhow!("no token account named {} for {}", token, multisig_name)),
+        },
+        None => Pubkey::from_str(matches.value_of("source").unwrap())?,
+    };
+    let target = Pubkey::from_str(matches.value_of("target").unwrap())?;
 
     let builder = client::request_builder::RequestBuilder::from(
         config.multisig.program_id(),
@@ -142,13 +156,30 @@ pub fn transfer_tokens(
         &*signer,
         None,
         RequestNamespace::Global,
-    );
+    )
+    .force(matches.is_present("force"));
+    let (mint, decimals) = builder.token_account_mint(&source)?;
+    println!("mint {} with {} decimals", mint, decimals);
+    let amount = client::amount::parse_amount(matches.value_of("amount").unwrap(), decimals)?;
+    let (target_account, exists) = builder.resolve_token_target(&target, &mint)?;
+    let create_target_owner = if exists {
+        None
+    } else if matches.is_present("create-recipient-ata") {
+        println!("creating ata {} for {}", target_account, target);
+        Some(target)
+    } else {
+        return Err(anyhow!(
+            "target token account {} does not exist, pass --create-recipient-ata to create it",
+            target_account
+        ));
+    };
     let res = builder.propose_transfer_tokens(
         multisig_config.account(),
         multisig_config.pda(),
         source,
-        target,
+        target_account,
         amount,
+        create_target_owner,
     );
     if res.is_err() {
         panic!("failed to submit proposal {:#?}", res.err().unwrap());
@@ -179,7 +210,8 @@ pub fn set_auth(
         &*signer,
         None,
         RequestNamespace::Global,
-    );
+    )
+    .force(matches.is_present("force"));
     match builder.propose_change_auth(
         &multisig_config.account(),
         &Pubkey::from_str(
@@ -213,7 +245,8 @@ pub fn submit_base64_bincode_ix(
             &*signer,
             None,
             RequestNamespace::Global,
-        );
+        )
+        .force(matches.is_present("force"));
         let res = builder.propose_blob_ix(
             multisig_config.account(),
             matches.value_of("ix-data").unwrap()
@@ -227,6 +260,55 @@ pub fn submit_base64_bincode_ix(
 Ok(())
 }
 
+pub fn submit_ix(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let input = match (matches.value_of("data"), matches.value_of("file")) {
+        (Some(data), _) => data.to_string(),
+        (None, Some(file)) => fs::read_to_string(file)?,
+        (None, None) => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        }
+    };
+    let ix = client::encoding::parse_instruction(
+        &input,
+        client::encoding::InstructionFormat::from_str(matches.value_of("format").unwrap())?,
+        client::encoding::Encoding::from_str(matches.value_of("encoding").unwrap())?,
+        usize::from_str(matches.value_of("index").unwrap())?,
+    )?;
+    println!("{}", client::decode::describe_instruction(&ix));
+
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    let res = builder.propose_solana_instruction(&multisig_config.account(), ix);
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    Ok(())
+}
+
 pub fn create_token_account(
     matches: &clap::ArgMatches,
     config_file_path: String,
@@ -275,3 +357,1321 @@ pub fn create_token_account(
 
     Ok(())
 }
+
+pub fn approve(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let tx = Pubkey::from_str(matches.value_of("tx").unwrap()).unwrap();
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    );
+    let res = builder.approve(multisig_config.account(), tx);
+    if res.is_err() {
+        panic!("failed to approve proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent tx {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn execute(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let tx = Pubkey::from_str(matches.value_of("tx").unwrap()).unwrap();
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    );
+    let res = builder.execute_transaction(multisig_config.account(), tx);
+    if res.is_err() {
+        panic!("failed to execute proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent tx {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn list_proposals(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    // nothing is signed, so any keypair will do as the payer
+    let payer = Keypair::generate(&mut OsRng);
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &payer,
+        None,
+        RequestNamespace::Global,
+    );
+    let multisig_account = builder.load_multisig(&multisig_config.account())?;
+    let proposals = builder.load_transactions(&multisig_config.account())?;
+    if proposals.is_empty() {
+        println!("no proposals found for {}", multisig_name);
+    }
+    for (address, proposal) in proposals.iter() {
+        let approvals = proposal.signers.iter().filter(|signed| **signed).count();
+        println!("proposal {}", address);
+        println!("\tprogram:   {}", proposal.program_id);
+        println!(
+            "\tapprovals: {}/{}",
+            approvals, multisig_account.threshold
+        );
+        println!("\texecuted:  {}", proposal.did_execute);
+        println!(
+            "\tstale:     {}",
+            proposal.owner_set_seqno != multisig_account.owner_set_seqno
+        );
+    }
+    Ok(())
+}
+
+pub fn show_proposal(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let tx = Pubkey::from_str(matches.value_of("tx").unwrap()).unwrap();
+    let payer = Keypair::generate(&mut OsRng);
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &payer,
+        None,
+        RequestNamespace::Global,
+    );
+    let proposal = builder.load_transaction(&tx)?;
+    let multisig_account = builder.load_multisig(&proposal.multisig)?;
+    let approvals = proposal.signers.iter().filter(|signed| **signed).count();
+    println!("proposal {}", tx);
+    println!("\tmultisig:  {}", proposal.multisig);
+    println!(
+        "\tapprovals: {}/{}",
+        approvals, multisig_account.threshold
+    );
+    println!("\texecuted:  {}", proposal.did_execute);
+    println!(
+        "\tstale:     {}",
+        proposal.owner_set_seqno != multisig_account.owner_set_seqno
+    );
+    let ix = client::decode::transaction_instruction(&proposal);
+    println!("{}", client::decode::describe_instruction(&ix));
+    Ok(())
+}
+
+pub fn propose_set_owners(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let owners = parse_owners(matches.value_of("owners").unwrap())?;
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    warn_pending_invalidated(&builder, &multisig_config)?;
+    let res = builder.propose_set_owners(multisig_config.account(), multisig_config.pda(), owners);
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn propose_change_threshold(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let threshold = u64::from_str(matches.value_of("threshold").unwrap())?;
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    let res = builder.propose_change_threshold(
+        multisig_config.account(),
+        multisig_config.pda(),
+        threshold,
+    );
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn propose_set_owners_and_threshold(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let owners = parse_owners(matches.value_of("owners").unwrap())?;
+    let threshold = u64::from_str(matches.value_of("threshold").unwrap())?;
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    warn_pending_invalidated(&builder, &multisig_config)?;
+    let res = builder.propose_set_owners_and_change_threshold(
+        multisig_config.account(),
+        multisig_config.pda(),
+        owners,
+        threshold,
+    );
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn sync(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let mut config = Configuration::load(config_file_path.as_str(), false)?;
+    let rpc = config.rpc_client();
+    let program_id = config.multisig.program_id();
+    let indexes: Vec<usize> = if matches.is_present("all") {
+        (0..config.multisig.accounts.len()).collect()
+    } else {
+        let multisig_name = matches.value_of("name").unwrap();
+        match config.multisig.multisig_index(multisig_name.to_string()) {
+            Some(idx) => vec![idx],
+            None => return Err(anyhow!("no multisig named {} in config", multisig_name)),
+        }
+    };
+    for idx in indexes {
+        let multisig_config = &mut config.multisig.accounts[idx];
+        if multisig_config.account.is_empty() {
+            println!("skipping {}, it has not been created yet", multisig_config.name);
+            continue;
+        }
+        let changes = sync_account(&rpc, &program_id, multisig_config)?;
+        if changes.is_empty() {
+            println!("{} is up to date", multisig_config.name);
+        } else {
+            println!("{} changed:", multisig_config.name);
+            for change in changes.iter() {
+                println!("\t{}", change);
+            }
+        }
+    }
+    config.save(&config_file_path, false)?;
+    Ok(())
+}
+
+/// overwrites the config entry with the on-chain multisig state, returning
+/// a description of every field that changed
+fn sync_account(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    multisig_config: &mut MultiSigAccount,
+) -> Result<Vec<String>> {
+    let data = rpc.get_account_data(&multisig_config.account())?;
+    let multisig = Multisig::try_deserialize(&mut data.as_slice())?;
+    let pda = Pubkey::create_program_address(
+        &[multisig_config.account().as_ref(), &[multisig.nonce]],
+        program_id,
+    )?;
+    let owners: Vec<String> = multisig.owners.iter().map(|owner| owner.to_string()).collect();
+    let mut changes = Vec::new();
+    for owner in owners.iter() {
+        if !multisig_config.owners.contains(owner) {
+            changes.push(format!("owner added: {}", owner));
+        }
+    }
+    for owner in multisig_config.owners.iter() {
+        if !owners.contains(owner) {
+            changes.push(format!("owner removed: {}", owner));
+        }
+    }
+    if multisig_config.threshold != multisig.threshold {
+        changes.push(format!(
+            "threshold: {} -> {}",
+            multisig_config.threshold, multisig.threshold
+        ));
+    }
+    if multisig_config.pda != pda.to_string() {
+        changes.push(format!("pda: {} -> {}", multisig_config.pda, pda));
+    }
+    if multisig_config.pda_nonce != multisig.nonce {
+        changes.push(format!(
+            "pda_nonce: {} -> {}",
+            multisig_config.pda_nonce, multisig.nonce
+        ));
+    }
+    multisig_config.owners = owners;
+    multisig_config.threshold = multisig.threshold;
+    multisig_config.pda = pda.to_string();
+    multisig_config.pda_nonce = multisig.nonce;
+    Ok(changes)
+}
+
+pub fn import(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let mut config = Configuration::load(config_file_path.as_str(), false)?;
+    let rpc = config.rpc_client();
+    let name = matches.value_of("name").unwrap();
+    if config.multisig.by_name(name.to_string()).is_some() {
+        return Err(anyhow!("multisig named {} already exists in config", name));
+    }
+    let account = Pubkey::from_str(matches.value_of("account").unwrap())?;
+    let data = rpc.get_account_data(&account)?;
+    let multisig = Multisig::try_deserialize(&mut data.as_slice())?;
+    let (pda, pda_nonce) =
+        Pubkey::find_program_address(&[account.as_ref()], &config.multisig.program_id());
+    if pda_nonce != multisig.nonce {
+        return Err(anyhow!(
+            "derived nonce {} does not match on-chain nonce {}",
+            pda_nonce,
+            multisig.nonce
+        ));
+    }
+    let token_accounts = rpc
+        .get_token_accounts_by_owner(&pda, TokenAccountsFilter::ProgramId(spl_token::id()))?
+        .into_iter()
+        .map(|keyed_account| {
+            let name = match &keyed_account.account.data {
+                UiAccountData::Json(parsed) => parsed.parsed["info"]["mint"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                _ => "".to_string(),
+            };
+            (name, keyed_account.pubkey)
+        })
+        .collect::<Vec<(String, String)>>();
+    config.multisig.accounts.push(MultiSigAccount {
+        name: name.to_string(),
+        account: account.to_string(),
+        threshold: multisig.threshold,
+        pda: pda.to_string(),
+        pda_nonce,
+        owners: multisig.owners.iter().map(|owner| owner.to_string()).collect(),
+        token_accounts,
+        programs: vec![],
+    });
+    config.save(&config_file_path, false)?;
+    println!("imported multisig {}", account);
+    println!("\tpda:       {}", pda);
+    println!("\tthreshold: {}/{}", multisig.threshold, multisig.owners.len());
+    println!(
+        "\tfound {} token accounts, named by their mint",
+        config.multisig.by_name(name.to_string()).unwrap().token_accounts.len()
+    );
+    Ok(())
+}
+
+pub fn propose_upgrade(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let program = resolve_program(&multisig_config, matches.value_of("program-id").unwrap())?;
+    let buffer = Pubkey::from_str(matches.value_of("buffer").unwrap())?;
+    let spill = match matches.value_of("spill") {
+        Some(spill) => Pubkey::from_str(spill)?,
+        None => signer.pubkey(),
+    };
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    let res = builder.propose_program_upgrade(multisig_config.account(), program, buffer, spill);
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn deploy_upgrade(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let program = resolve_program(&multisig_config, matches.value_of("program-id").unwrap())?;
+    let so_path = matches.value_of("so").unwrap();
+    let program_data = fs::read(so_path)?;
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+
+    // the buffer keypair is persisted so an interrupted write can be resumed
+    let buffer_keypair_path = format!("{}.buffer.json", so_path);
+    let buffer = if Path::new(&buffer_keypair_path).exists() {
+        let buffer = read_keypair_file(&buffer_keypair_path)
+            .map_err(|err| anyhow!("failed to read buffer keypair: {}", err))?;
+        println!("resuming with buffer {}", buffer.pubkey());
+        buffer
+    } else {
+        let buffer = Keypair::generate(&mut OsRng);
+        write_keypair_file(&buffer, &buffer_keypair_path)
+            .map_err(|err| anyhow!("failed to write buffer keypair: {}", err))?;
+        println!("using new buffer {}", buffer.pubkey());
+        buffer
+    };
+
+    let pda = checked_pda(&builder, &multisig_config)?;
+
+    // only a missing account means the buffer still has to be created, any
+    // other rpc failure is surfaced instead of clobbering the resume state
+    let existing = builder
+        .rpc
+        .get_account_with_commitment(&buffer.pubkey(), builder.rpc.commitment())?
+        .value;
+    let authority = if let Some(existing) = existing {
+        let expected_len = UpgradeableLoaderState::buffer_len(program_data.len())?;
+        if existing.data.len() != expected_len {
+            return Err(anyhow!(
+                "buffer {} holds {} bytes but {} needs {}, remove {} to start over",
+                buffer.pubkey(),
+                existing.data.len(),
+                so_path,
+                expected_len,
+                buffer_keypair_path
+            ));
+        }
+        builder.buffer_authority(&buffer.pubkey())?
+    } else {
+        println!("creating buffer {}", buffer.pubkey());
+        builder.create_buffer(&buffer, program_data.len())?;
+        Some(signer.pubkey())
+    };
+    if authority == Some(signer.pubkey()) {
+        println!("writing {} bytes to buffer", program_data.len());
+        builder.write_buffer(&buffer.pubkey(), &program_data)?;
+        println!("transferring buffer authority to multisig pda {}", pda);
+        builder.set_buffer_authority(&buffer.pubkey(), &pda)?;
+    } else if authority == Some(pda) {
+        // the write can no longer be redone, so a buffer left over from an
+        // earlier build must not be proposed in place of this one
+        let buffer_data = builder.rpc.get_account_data(&buffer.pubkey())?;
+        let buffer_hash = account_program_hash(
+            &buffer_data,
+            UpgradeableLoaderState::buffer_data_offset()?,
+            program_data.len(),
+        )?;
+        if buffer_hash != program_hash(&program_data) {
+            return Err(anyhow!(
+                "buffer {} already belongs to the multisig but does not match {}, remove {} to start over",
+                buffer.pubkey(),
+                so_path,
+                buffer_keypair_path
+            ));
+        }
+        println!("buffer already belongs to the multisig and matches the local build, skipping write");
+    } else {
+        return Err(anyhow!(
+            "buffer {} has unexpected authority {:?}",
+            buffer.pubkey(),
+            authority
+        ));
+    }
+
+    let res = builder.propose_program_upgrade(
+        multisig_config.account(),
+        program,
+        buffer.pubkey(),
+        signer.pubkey(),
+    );
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    fs::remove_file(&buffer_keypair_path)?;
+    Ok(())
+}
+
+pub fn verify_upgrade(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let rpc = config.rpc_client();
+    let tx = Pubkey::from_str(matches.value_of("tx").unwrap())?;
+    let data = rpc.get_account_data(&tx)?;
+    let proposal = serum_multisig::Transaction::try_deserialize(&mut data.as_slice())?;
+    if proposal.program_id != bpf_loader_upgradeable::id() {
+        return Err(anyhow!("proposal {} does not target the upgradeable loader", tx));
+    }
+    match bincode::deserialize(&proposal.data)? {
+        UpgradeableLoaderInstruction::Upgrade => {}
+        _ => return Err(anyhow!("proposal {} is not a program upgrade", tx)),
+    }
+    // upgrade accounts are [programdata, program, buffer, spill, rent, clock, authority]
+    if proposal.accounts.len() < 3 {
+        return Err(anyhow!("proposal {} is missing upgrade accounts", tx));
+    }
+    let program = proposal.accounts[1].pubkey;
+    let buffer = proposal.accounts[2].pubkey;
+
+    let local_program = fs::read(matches.value_of("so").unwrap())?;
+    let local_hash = program_hash(&local_program);
+    let buffer_data = rpc.get_account_data(&buffer)?;
+    println!("program: {}", program);
+    println!("local build sha256: {}", local_hash);
+    let buffer_hash = account_program_hash(
+        &buffer_data,
+        UpgradeableLoaderState::buffer_data_offset()?,
+        local_program.len(),
+    )
+    .map_err(|err| anyhow!("buffer {} does not match the local build: {}", buffer, err))?;
+    println!("buffer {} sha256: {}", buffer, buffer_hash);
+
+    if matches.is_present("check-deployed") {
+        let (programdata, _) =
+            Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id());
+        let programdata_data = rpc.get_account_data(&programdata)?;
+        match account_program_hash(
+            &programdata_data,
+            UpgradeableLoaderState::programdata_data_offset()?,
+            local_program.len(),
+        ) {
+            Ok(deployed_hash) => {
+                println!("deployed {} sha256: {}", programdata, deployed_hash);
+                if deployed_hash == local_hash {
+                    println!("the local build is already deployed");
+                }
+            }
+            Err(err) => println!("deployed {} differs from the local build: {}", programdata, err),
+        }
+    }
+
+    if buffer_hash != local_hash {
+        return Err(anyhow!("buffer contents do not match the local build"));
+    }
+    println!("buffer contents match the local build");
+    Ok(())
+}
+
+/// hex encoded sha256 of the program bytes
+fn program_hash(program: &[u8]) -> String {
+    hash(program)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// hashes the `len` program bytes stored at `offset` of a buffer or program
+/// data account, requiring the rest of the account to be zero padding
+fn account_program_hash(data: &[u8], offset: usize, len: usize) -> Result<String> {
+    let program = data.get(offset..offset + len).ok_or_else(|| {
+        anyhow!(
+            "account holds {} program bytes, expected at least {}",
+            data.len().saturating_sub(offset),
+            len
+        )
+    })?;
+    if data[offset + len..].iter().any(|byte| *byte != 0) {
+        return Err(anyhow!("account has non-zero bytes past the program end"));
+    }
+    Ok(program_hash(program))
+}
+
+pub fn propose_set_upgrade_authority(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let program = resolve_program(&multisig_config, matches.value_of("program-id").unwrap())?;
+    let new_authority = if matches.is_present("final") {
+        println!(
+            "WARNING: this proposal makes program {} immutable, it can never be upgraded again",
+            program
+        );
+        println!("type the program id to confirm:");
+        let mut confirmation = String::new();
+        io::stdin().read_line(&mut confirmation)?;
+        if confirmation.trim() != program.to_string() {
+            return Err(anyhow!("confirmation did not match program id, aborting"));
+        }
+        None
+    } else {
+        Some(Pubkey::from_str(matches.value_of("new-authority").unwrap())?)
+    };
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    let res = builder.propose_set_upgrade_authority(multisig_config.account(), program, new_authority);
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn adopt_program(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let mut config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let multisig_idx = config
+        .multisig
+        .multisig_index(multisig_name.to_string())
+        .unwrap();
+    let program = Pubkey::from_str(matches.value_of("program-id").unwrap())?;
+    let program_name = matches.value_of("program-name").unwrap();
+    if multisig_config.program_by_name(program_name).is_some() {
+        return Err(anyhow!("program named {} already exists", program_name));
+    }
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    );
+    let pda = checked_pda(&builder, &multisig_config)?;
+    let authority = builder.upgrade_authority(&program)?;
+    if authority == Some(pda) {
+        println!("program is already owned by the multisig pda");
+    } else if authority == Some(signer.pubkey()) {
+        let sig = builder.set_upgrade_authority(&program, &pda)?;
+        println!("sent tx {}", sig);
+    } else {
+        return Err(anyhow!(
+            "program upgrade authority is {:?}, expected signer {}",
+            authority,
+            signer.pubkey()
+        ));
+    }
+    config.multisig.accounts[multisig_idx]
+        .programs
+        .push((program_name.to_string(), program.to_string()));
+    config.save(&config_file_path, false)?;
+    Ok(())
+}
+
+/// derives the multisig pda from the on-chain nonce, refusing to continue when
+/// it differs from the pda recorded in the config
+fn checked_pda(
+    builder: &client::request_builder::RequestBuilder,
+    multisig_config: &MultiSigAccount,
+) -> Result<Pubkey> {
+    let pda = builder.multisig_signer(&multisig_config.account())?;
+    if pda != multisig_config.pda() {
+        return Err(anyhow!(
+            "on-chain multisig pda {} does not match configured pda {}",
+            pda,
+            multisig_config.pda()
+        ));
+    }
+    Ok(pda)
+}
+
+/// resolves a program by its adopted name, falling back to parsing an address
+fn resolve_program(multisig_config: &MultiSigAccount, program: &str) -> Result<Pubkey> {
+    match multisig_config.program_by_name(program) {
+        Some(program_id) => Ok(program_id),
+        None => Pubkey::from_str(program)
+            .map_err(|_| anyhow!("{} is neither an adopted program nor an address", program)),
+    }
+}
+
+pub fn transfer_sol(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let target = Pubkey::from_str(matches.value_of("target").unwrap())?;
+    let lamports = client::amount::parse_amount(
+        matches.value_of("amount").unwrap(),
+        client::amount::SOL_DECIMALS,
+    )?;
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    let balance = builder.rpc.get_balance(&multisig_config.pda())?;
+    if lamports > balance {
+        println!(
+            "WARNING: transfer of {} lamports exceeds the pda balance of {} lamports, execution will fail unless it is funded",
+            lamports, balance
+        );
+    }
+    let res = builder.propose_transfer_sol(
+        multisig_config.account(),
+        multisig_config.pda(),
+        target,
+        lamports,
+    );
+    if res.is_err() {
+        panic!("failed to submit proposal {:#?}", res.err().unwrap());
+    } else {
+        println!("sent proposal, account: {}", res.unwrap());
+    }
+    Ok(())
+}
+
+pub fn balances(matches: &clap::ArgMatches, config_file_path: String) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let multisigs = match matches.value_of("name") {
+        Some(multisig_name) => match config.multisig.by_name(multisig_name.to_string()) {
+            Some(multisig_config) => vec![multisig_config],
+            None => return Err(anyhow!("no multisig named {} in config", multisig_name)),
+        },
+        None => config.multisig.accounts.clone(),
+    };
+    let payer = Keypair::generate(&mut OsRng);
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &payer,
+        None,
+        RequestNamespace::Global,
+    );
+    for multisig_config in multisigs.iter() {
+        if multisig_config.pda.is_empty() {
+            println!("skipping {}, it has not been created yet", multisig_config.name);
+            continue;
+        }
+        let pda = multisig_config.pda();
+        let lamports = builder.rpc.get_balance(&pda)?;
+        println!("{} (pda {})", multisig_config.name, pda);
+        println!(
+            "\tSOL: {}",
+            client::amount::format_amount(lamports, client::amount::SOL_DECIMALS)
+        );
+        for balance in builder.token_balances(&pda)?.iter() {
+            let name = multisig_config
+                .token_accounts
+                .iter()
+                .find(|(_, address)| address.eq(&balance.address.to_string()))
+                .map(|(name, _)| name.clone());
+            println!(
+                "\t{}: {} (account {}, mint {}){}",
+                name.clone().unwrap_or_else(|| "unnamed".to_string()),
+                client::amount::format_amount(balance.amount, balance.decimals),
+                balance.address,
+                balance.mint,
+                if name.is_none() { " [not in config]" } else { "" }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// a validated row of a batch transfer file
+struct Payout {
+    recipient: String,
+    token: String,
+    amount: String,
+    source: Pubkey,
+    target: Pubkey,
+    raw_amount: u64,
+}
+
+pub fn batch_transfer(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let file = matches.value_of("file").unwrap();
+    let output = match matches.value_of("output") {
+        Some(output) => output.to_string(),
+        None => format!("{}.results.csv", file),
+    };
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    )
+    .force(matches.is_present("force"));
+    let balances = builder.token_balances(&multisig_config.pda())?;
+
+    // validate every row before anything is proposed
+    let mut payouts: Vec<Payout> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(file)?;
+    for (idx, record) in reader.records().enumerate() {
+        // the header is line 1
+        let line = idx + 2;
+        let record = record?;
+        if record.len() != 3 {
+            errors.push(format!("line {}: expected 3 columns, found {}", line, record.len()));
+            continue;
+        }
+        match validate_payout(&builder, &multisig_config, &balances, &record[0], &record[1], &record[2]) {
+            Ok(payout) => {
+                // separate payouts to the same recipient are fine, only an
+                // identical row is assumed to be pasted twice by mistake
+                if payouts.iter().any(|other| {
+                    other.source == payout.source
+                        && other.target == payout.target
+                        && other.raw_amount == payout.raw_amount
+                }) {
+                    errors.push(format!(
+                        "line {}: duplicate row paying {} of {} to {}",
+                        line, payout.amount, payout.token, payout.recipient
+                    ));
+                } else {
+                    payouts.push(payout);
+                }
+            }
+            Err(err) => errors.push(format!("line {}: {}", line, err)),
+        }
+    }
+    for balance in balances.iter() {
+        let total = payouts
+            .iter()
+            .filter(|payout| payout.source == balance.address)
+            .try_fold(0u64, |total, payout| total.checked_add(payout.raw_amount));
+        match total {
+            Some(total) if total <= balance.amount => {}
+            _ => errors.push(format!(
+                "payouts from {} exceed its balance of {}",
+                balance.address,
+                client::amount::format_amount(balance.amount, balance.decimals)
+            )),
+        }
+    }
+    if !errors.is_empty() {
+        for err in errors.iter() {
+            println!("{}", err);
+        }
+        return Err(anyhow!("{} rows failed validation, nothing was proposed", errors.len()));
+    }
+
+    let mut writer = csv::Writer::from_path(&output)?;
+    writer.write_record(&["recipient", "token", "amount", "proposal", "error"])?;
+    for payout in payouts.iter() {
+        let res = builder.propose_transfer_tokens(
+            multisig_config.account(),
+            multisig_config.pda(),
+            payout.source,
+            payout.target,
+            payout.raw_amount,
+            None,
+        );
+        let (proposal, error) = match res {
+            Ok(proposal) => {
+                println!("sent proposal {} paying {} {} to {}", proposal, payout.amount, payout.token, payout.recipient);
+                (proposal.to_string(), "".to_string())
+            }
+            Err(err) => {
+                println!("failed to propose payout to {}: {:#?}", payout.recipient, err);
+                ("".to_string(), err.to_string())
+            }
+        };
+        writer.write_record(&[
+            payout.recipient.as_str(),
+            payout.token.as_str(),
+            payout.amount.as_str(),
+            proposal.as_str(),
+            error.as_str(),
+        ])?;
+        writer.flush()?;
+    }
+    println!("wrote results to {}", output);
+    Ok(())
+}
+
+/// resolves the source and target token accounts of a payout row and parses
+/// its amount with the decimals of the mint
+fn validate_payout(
+    builder: &client::request_builder::RequestBuilder,
+    multisig_config: &MultiSigAccount,
+    balances: &[client::request_builder::TokenBalance],
+    recipient: &str,
+    token: &str,
+    amount: &str,
+) -> Result<Payout> {
+    let recipient_key = Pubkey::from_str(recipient)
+        .map_err(|_| anyhow!("invalid recipient {}", recipient))?;
+    let source = match multisig_config.token_account_by_name(token) {
+        Some(source) => balances.iter().find(|balance| balance.address == source),
+        None => {
+            let mint = Pubkey::from_str(token)
+                .map_err(|_| anyhow!("{} is neither a configured token nor a mint", token))?;
+            // prefer the pda's ata when it holds more than one account for the mint
+            let ata = spl_associated_token_account::get_associated_token_address(
+                &multisig_config.pda(),
+                &mint,
+            );
+            balances
+                .iter()
+                .find(|balance| balance.address == ata)
+                .or_else(|| balances.iter().find(|balance| balance.mint == mint))
+        }
+    };
+    let source = source.ok_or_else(|| anyhow!("the multisig holds no token account for {}", token))?;
+    let raw_amount = client::amount::parse_amount(amount, source.decimals)?;
+    let (target, exists) = builder.resolve_token_target(&recipient_key, &source.mint)?;
+    if !exists {
+        return Err(anyhow!("recipient token account {} does not exist", target));
+    }
+    Ok(Payout {
+        recipient: recipient.to_string(),
+        token: token.to_string(),
+        amount: amount.to_string(),
+        source: source.address,
+        target,
+        raw_amount,
+    })
+}
+
+pub fn approve_all(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let program_filter = match matches.value_of("filter") {
+        Some(filter) => match filter.split_once('=') {
+            Some(("program", program)) => Some(Pubkey::from_str(program)?),
+            _ => return Err(anyhow!("unsupported filter {}, expected program=<id>", filter)),
+        },
+        None => None,
+    };
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    );
+    let multisig_account = builder.load_multisig(&multisig_config.account())?;
+    let owner_idx = match multisig_account
+        .owners
+        .iter()
+        .position(|owner| *owner == signer.pubkey())
+    {
+        Some(owner_idx) => owner_idx,
+        None => return Err(anyhow!("signer {} is not an owner of {}", signer.pubkey(), multisig_name)),
+    };
+    let mut to_approve: Vec<Pubkey> = Vec::new();
+    for (address, proposal) in builder.load_transactions(&multisig_config.account())?.iter() {
+        if proposal.did_execute || proposal.owner_set_seqno != multisig_account.owner_set_seqno {
+            continue;
+        }
+        if proposal.signers.get(owner_idx).cloned().unwrap_or(false) {
+            continue;
+        }
+        if let Some(program) = program_filter {
+            if proposal.program_id != program {
+                continue;
+            }
+        }
+        let approvals = proposal.signers.iter().filter(|signed| **signed).count();
+        println!("proposal {} ({}/{} approvals)", address, approvals, multisig_account.threshold);
+        let ix = client::decode::transaction_instruction(proposal);
+        println!("{}", client::decode::describe_instruction(&ix));
+        if matches.is_present("yes") || confirm("approve this proposal? [y/N]")? {
+            to_approve.push(*address);
+        }
+    }
+    if to_approve.is_empty() {
+        println!("nothing to approve");
+        return Ok(());
+    }
+    println!("approving {} proposals", to_approve.len());
+    let res = builder.approve_many(multisig_config.account(), &to_approve);
+    if res.is_err() {
+        panic!("failed to approve proposals {:#?}", res.err().unwrap());
+    } else {
+        for sig in res.unwrap().iter() {
+            println!("sent tx {}", sig);
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_ready(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("signer {}", signer.pubkey());
+    let multisig_name = matches.value_of("name").unwrap();
+    let multisig_config = config.multisig.by_name(multisig_name.to_string()).unwrap();
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    );
+    let multisig_account = builder.load_multisig(&multisig_config.account())?;
+    let ready: Vec<Pubkey> = builder
+        .load_transactions(&multisig_config.account())?
+        .into_iter()
+        .filter(|(_, proposal)| {
+            let approvals = proposal.signers.iter().filter(|signed| **signed).count() as u64;
+            !proposal.did_execute
+                && proposal.owner_set_seqno == multisig_account.owner_set_seqno
+                && approvals >= multisig_account.threshold
+        })
+        .map(|(address, _)| address)
+        .collect();
+    if ready.is_empty() {
+        println!("no proposals are ready to execute");
+        return Ok(());
+    }
+    let (mut executed, mut failed) = (0, 0);
+    for address in ready.iter() {
+        println!("proposal {}", address);
+        match builder.simulate_execute_transaction(multisig_config.account(), *address) {
+            Ok(simulation) if simulation.err.is_none() => {}
+            Ok(simulation) => {
+                println!("\tsimulation failed, skipping: {:?}", simulation.err);
+                for log in simulation.logs.unwrap_or_default().iter() {
+                    println!("\t\t{}", log);
+                }
+                failed += 1;
+                continue;
+            }
+            Err(err) => {
+                println!("\tsimulation failed, skipping: {:#?}", err);
+                failed += 1;
+                continue;
+            }
+        }
+        match builder.execute_transaction(multisig_config.account(), *address) {
+            Ok(sig) => {
+                println!("\texecuted, tx {}", sig);
+                executed += 1;
+            }
+            Err(err) => {
+                println!("\texecution failed: {:#?}", err);
+                failed += 1;
+            }
+        }
+    }
+    println!("executed {} proposals, {} failed", executed, failed);
+    Ok(())
+}
+
+pub fn simulate(
+    matches: &clap::ArgMatches,
+    config_file_path: String,
+    keypair: String,
+) -> Result<()> {
+    let config = Configuration::load(config_file_path.as_str(), false)?;
+    let mut wallet_manager = remote_wallet::maybe_wallet_manager().unwrap();
+    let signer = signer_from_path(matches, &keypair, &keypair, &mut wallet_manager);
+    if signer.is_err() {
+        panic!("failed to get signer {:#?}", signer.err().unwrap());
+    }
+    let signer = signer.unwrap();
+    println!("fee payer {}", signer.pubkey());
+    let tx = Pubkey::from_str(matches.value_of("tx").unwrap())?;
+    let builder = client::request_builder::RequestBuilder::from(
+        config.multisig.program_id(),
+        config.rpc_url.as_str(),
+        &*signer,
+        None,
+        RequestNamespace::Global,
+    );
+    let proposal = builder.load_transaction(&tx)?;
+    let multisig_account = builder.load_multisig(&proposal.multisig)?;
+    let approvals = proposal.signers.iter().filter(|signed| **signed).count() as u64;
+    let ix = if approvals >= multisig_account.threshold {
+        builder.execute_transaction_instruction(proposal.multisig, tx)?
+    } else {
+        // the multisig program refuses to execute below the threshold, so
+        // simulate the inner instruction signed by the pda instead
+        println!(
+            "proposal has {}/{} approvals, simulating its instruction directly",
+            approvals, multisig_account.threshold
+        );
+        let pda = builder.multisig_signer(&proposal.multisig)?;
+        let mut ix = client::decode::transaction_instruction(&proposal);
+        for meta in ix.accounts.iter_mut() {
+            if meta.pubkey == pda {
+                meta.is_signer = true;
+            }
+        }
+        ix
+    };
+    let mut writable: Vec<Pubkey> = ix
+        .accounts
+        .iter()
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+    writable.sort();
+    writable.dedup();
+    // the simulation can't return the accounts as they were before it ran, so
+    // they are read separately at the same commitment and may be a few slots older
+    let pre = builder.get_multiple_accounts(&writable)?;
+    let simulation = builder.simulate_instructions_with_accounts(&[ix], &writable)?;
+    println!("program logs:");
+    for log in simulation.logs.clone().unwrap_or_default().iter() {
+        println!("\t{}", log);
+    }
+    if let Some(err) = &simulation.err {
+        return Err(anyhow!("simulation failed: {:?}", err));
+    }
+    let post: Vec<Option<Account>> = simulation
+        .accounts
+        .unwrap_or_default()
+        .iter()
+        .map(|account| account.as_ref().and_then(|account| account.decode::<Account>()))
+        .collect();
+    println!("account changes:");
+    println!(
+        "\t(before balances are read separately from the simulation, so they may be from an \
+         earlier slot and include changes made by other transactions)"
+    );
+    for (idx, address) in writable.iter().enumerate() {
+        let before = pre.get(idx).cloned().flatten();
+        let after = post.get(idx).cloned().flatten();
+        let lamports_before = before.as_ref().map(|account| account.lamports).unwrap_or(0);
+        let lamports_after = after.as_ref().map(|account| account.lamports).unwrap_or(0);
+        let tokens_before = before.as_ref().and_then(token_amount);
+        let tokens_after = after.as_ref().and_then(token_amount);
+        if lamports_before == lamports_after && tokens_before == tokens_after {
+            continue;
+        }
+        println!("\t{}", address);
+        if lamports_before != lamports_after {
+            println!(
+                "\t\tSOL: {} -> {}",
+                client::amount::format_amount(lamports_before, client::amount::SOL_DECIMALS),
+                client::amount::format_amount(lamports_after, client::amount::SOL_DECIMALS)
+            );
+        }
+        if tokens_before != tokens_after {
+            let mint = tokens_after.or(tokens_before).unwrap().0;
+            let decimals =
+                spl_token::state::Mint::unpack(&builder.rpc.get_account_data(&mint)?)?.decimals;
+            println!(
+                "\t\ttokens of mint {}: {} -> {}",
+                mint,
+                client::amount::format_amount(tokens_before.map(|(_, amount)| amount).unwrap_or(0), decimals),
+                client::amount::format_amount(tokens_after.map(|(_, amount)| amount).unwrap_or(0), decimals)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// returns the mint and amount held by an spl token account
+fn token_amount(account: &Account) -> Option<(Pubkey, u64)> {
+    if account.owner != spl_token::id() {
+        return None;
+    }
+    spl_token::state::Account::unpack(&account.data)
+        .ok()
+        .map(|token_account| (token_account.mint, token_account.amount))
+}
+
+/// prompts on stdout and returns true if the answer starts with y
+fn confirm(prompt: &str) -> Result<bool> {
+    println!("{}", prompt);
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_lowercase().starts_with('y'))
+}
+
+/// parses a comma separated list of owners, rejecting empty lists and duplicates
+fn parse_owners(owners: &str) -> Result<Vec<Pubkey>> {
+    let mut parsed: Vec<Pubkey> = Vec::new();
+    for owner in owners.split(",").map(|owner| owner.trim()) {
+        if owner.is_empty() {
+            continue;
+        }
+        let owner = Pubkey::from_str(owner).map_err(|err| anyhow!("invalid owner {}: {}", owner, err))?;
+        if parsed.contains(&owner) {
+            return Err(anyhow!("duplicate owner {}", owner));
+        }
+        parsed.push(owner);
+    }
+    if parsed.is_empty() {
+        return Err(anyhow!("at least one owner is required"));
+    }
+    Ok(parsed)
+}
+
+/// changing the owner set bumps owner_set_seqno, so warn about every
+/// pending proposal that will no longer be executable
+fn warn_pending_invalidated(
+    builder: &client::request_builder::RequestBuilder,
+    multisig_config: &MultiSigAccount,
+) -> Result<()> {
+    let multisig_account = builder.load_multisig(&multisig_config.account())?;
+    let pending: Vec<Pubkey> = builder
+        .load_transactions(&multisig_config.account())?
+        .into_iter()
+        .filter(|(_, proposal)| {
+            !proposal.did_execute && proposal.owner_set_seqno == multisig_account.owner_set_seqno
+        })
+        .map(|(address, _)| address)
+        .collect();
+    println!(
+        "WARNING: executing this proposal bumps the owner_set_seqno, invalidating all {} pending proposals",
+        pending.len()
+    );
+    for address in pending.iter() {
+        println!("\t{}", address);
+    }
+    Ok(())
+}
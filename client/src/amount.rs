@@ -0,0 +1,108 @@
+//! exact conversion of decimal ui amounts into raw token amounts
+
+use anyhow::{anyhow, Result};
+
+/// the number of decimals used when denominating lamports in SOL
+pub const SOL_DECIMALS: u8 = 9;
+
+/// converts a decimal string such as `1.5` into a raw amount with the given
+/// number of decimals, rejecting excess precision and overflow instead of
+/// rounding the way a floating point conversion would
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<u64> {
+    let amount = amount.trim();
+    let (whole, fraction) = match amount.find('.') {
+        Some(idx) => (&amount[..idx], &amount[idx + 1..]),
+        None => (amount, ""),
+    };
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(anyhow!("invalid amount '{}'", amount));
+    }
+    if !whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(anyhow!("invalid amount '{}'", amount));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(anyhow!(
+            "amount '{}' has more than {} decimal places",
+            amount,
+            decimals
+        ));
+    }
+    let padding = std::iter::repeat('0').take(decimals as usize - fraction.len());
+    let mut value: u64 = 0;
+    for digit in whole.chars().chain(fraction.chars()).chain(padding) {
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(digit as u64 - '0' as u64))
+            .ok_or_else(|| anyhow!("amount '{}' is too large", amount))?;
+    }
+    Ok(value)
+}
+
+/// formats a raw amount as an exact decimal string with the given number of
+/// decimals, trimming trailing zeros from the fractional part
+pub fn format_amount(amount: u64, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_whole_and_fractional_amounts() {
+        assert_eq!(parse_amount("1", 6).unwrap(), 1_000_000);
+        assert_eq!(parse_amount("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(parse_amount(".5", 6).unwrap(), 500_000);
+        assert_eq!(parse_amount("5.", 6).unwrap(), 5_000_000);
+        assert_eq!(parse_amount("0", 0).unwrap(), 0);
+        assert_eq!(parse_amount("0.000000001", 9).unwrap(), 1);
+        assert_eq!(
+            parse_amount("123456789.123456789", 9).unwrap(),
+            123_456_789_123_456_789
+        );
+    }
+
+    #[test]
+    fn rejects_excess_precision() {
+        assert!(parse_amount("0.000000001", 8).is_err());
+        assert!(parse_amount("1.5", 0).is_err());
+    }
+
+    #[test]
+    fn handles_u64_bounds() {
+        assert_eq!(parse_amount("18446744073709551615", 0).unwrap(), u64::MAX);
+        assert_eq!(parse_amount("18446744073.709551615", 9).unwrap(), u64::MAX);
+        assert!(parse_amount("18446744073709551616", 0).is_err());
+        assert!(parse_amount("18446744073.709551616", 9).is_err());
+        assert!(parse_amount("18446744074", 9).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        assert!(parse_amount("", 6).is_err());
+        assert!(parse_amount(".", 6).is_err());
+        assert!(parse_amount("-1", 6).is_err());
+        assert!(parse_amount("1e6", 6).is_err());
+        assert!(parse_amount("1.2.3", 6).is_err());
+    }
+
+    #[test]
+    fn formats_amounts() {
+        assert_eq!(format_amount(0, 6), "0");
+        assert_eq!(format_amount(1, 9), "0.000000001");
+        assert_eq!(format_amount(1_500_000, 6), "1.5");
+        assert_eq!(format_amount(42, 0), "42");
+        assert_eq!(format_amount(u64::MAX, 9), "18446744073.709551615");
+    }
+}
@@ -0,0 +1,403 @@
+//! human readable descriptions of the instructions stored in multisig transactions
+
+use anchor_client::solana_sdk::bpf_loader_upgradeable;
+use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction};
+use anchor_client::solana_sdk::loader_upgradeable_instruction::UpgradeableLoaderInstruction;
+use anchor_client::solana_sdk::system_instruction::SystemInstruction;
+use anchor_client::solana_sdk::system_program;
+use spl_token::instruction::TokenInstruction;
+
+use crate::amount::{format_amount, SOL_DECIMALS};
+
+/// rebuilds the instruction stored within a multisig transaction account
+pub fn transaction_instruction(transaction: &serum_multisig::Transaction) -> Instruction {
+    Instruction {
+        program_id: transaction.program_id,
+        accounts: transaction
+            .accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: transaction.data.clone(),
+    }
+}
+
+/// returns a human readable description of the instruction, falling back to
+/// a raw dump of the instruction data when the program or data isn't known
+pub fn describe_instruction(ix: &Instruction) -> String {
+    let description = if ix.program_id == spl_token::id() {
+        describe_token_instruction(ix)
+    } else if ix.program_id == system_program::id() {
+        describe_system_instruction(ix)
+    } else if ix.program_id == bpf_loader_upgradeable::id() {
+        describe_upgradeable_loader_instruction(ix)
+    } else {
+        None
+    };
+    match description {
+        Some(description) => description,
+        None => describe_raw(ix),
+    }
+}
+
+fn describe_token_instruction(ix: &Instruction) -> Option<String> {
+    let token_ix = TokenInstruction::unpack(&ix.data).ok()?;
+    let description = match token_ix {
+        TokenInstruction::Transfer { amount } => format!(
+            "spl-token transfer {} (raw amount) from {} to {}, authority {}",
+            amount,
+            account(ix, 0),
+            account(ix, 1),
+            account(ix, 2)
+        ),
+        TokenInstruction::TransferChecked { amount, decimals } => format!(
+            "spl-token transfer {} of mint {} from {} to {}, authority {}",
+            format_amount(amount, decimals),
+            account(ix, 1),
+            account(ix, 0),
+            account(ix, 2),
+            account(ix, 3)
+        ),
+        TokenInstruction::SetAuthority {
+            authority_type,
+            new_authority,
+        } => format!(
+            "spl-token set {:?} authority of {} from {} to {}",
+            authority_type,
+            account(ix, 0),
+            account(ix, 1),
+            new_authority
+                .map(|authority| authority.to_string())
+                .unwrap_or_else(|| "none".to_string())
+        ),
+        TokenInstruction::CloseAccount => format!(
+            "spl-token close account {}, sending lamports to {}, authority {}",
+            account(ix, 0),
+            account(ix, 1),
+            account(ix, 2)
+        ),
+        TokenInstruction::MintTo { amount } => format!(
+            "spl-token mint {} (raw amount) of mint {} to {}, authority {}",
+            amount,
+            account(ix, 0),
+            account(ix, 1),
+            account(ix, 2)
+        ),
+        TokenInstruction::Burn { amount } => format!(
+            "spl-token burn {} (raw amount) of mint {} from {}, authority {}",
+            amount,
+            account(ix, 1),
+            account(ix, 0),
+            account(ix, 2)
+        ),
+        other => format!("spl-token {:?}", other),
+    };
+    Some(description)
+}
+
+fn describe_system_instruction(ix: &Instruction) -> Option<String> {
+    let system_ix: SystemInstruction = bincode::deserialize(&ix.data).ok()?;
+    let description = match system_ix {
+        SystemInstruction::Transfer { lamports } => format!(
+            "system transfer {} SOL from {} to {}",
+            format_amount(lamports, SOL_DECIMALS),
+            account(ix, 0),
+            account(ix, 1)
+        ),
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => format!(
+            "system create account {} funded by {} with {} SOL, {} bytes, owned by {}",
+            account(ix, 1),
+            account(ix, 0),
+            format_amount(lamports, SOL_DECIMALS),
+            space,
+            owner
+        ),
+        SystemInstruction::Assign { owner } => {
+            format!("system assign {} to program {}", account(ix, 0), owner)
+        }
+        other => format!("system {:?}", other),
+    };
+    Some(description)
+}
+
+fn describe_upgradeable_loader_instruction(ix: &Instruction) -> Option<String> {
+    let loader_ix: UpgradeableLoaderInstruction = bincode::deserialize(&ix.data).ok()?;
+    let description = match loader_ix {
+        UpgradeableLoaderInstruction::Upgrade => format!(
+            "bpf_loader_upgradeable upgrade program {} from buffer {}, spill to {}, authority {}",
+            account(ix, 1),
+            account(ix, 2),
+            account(ix, 3),
+            account(ix, 6)
+        ),
+        // set_buffer_authority and set_upgrade_authority share this instruction,
+        // the first account is either the buffer or the program data account
+        UpgradeableLoaderInstruction::SetAuthority => format!(
+            "bpf_loader_upgradeable set authority of {} (buffer or program data) from {} to {}",
+            account(ix, 0),
+            account(ix, 1),
+            if ix.accounts.len() > 2 {
+                account(ix, 2)
+            } else {
+                "none (immutable)".to_string()
+            }
+        ),
+        UpgradeableLoaderInstruction::Write { offset, bytes } => format!(
+            "bpf_loader_upgradeable write {} bytes at offset {} to buffer {}",
+            bytes.len(),
+            offset,
+            account(ix, 0)
+        ),
+        other => format!("bpf_loader_upgradeable {:?}", other),
+    };
+    Some(description)
+}
+
+fn describe_raw(ix: &Instruction) -> String {
+    let hex: String = ix.data.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let mut description = format!(
+        "unknown instruction for program {}\n\tdata (hex):    {}\n\tdata (base64): {}",
+        ix.program_id,
+        hex,
+        base64::encode(&ix.data)
+    );
+    for (idx, meta) in ix.accounts.iter().enumerate() {
+        description.push_str(&format!(
+            "\n\taccount {}: {} (signer: {}, writable: {})",
+            idx, meta.pubkey, meta.is_signer, meta.is_writable
+        ));
+    }
+    description
+}
+
+fn account(ix: &Instruction, idx: usize) -> String {
+    match ix.accounts.get(idx) {
+        Some(meta) => meta.pubkey.to_string(),
+        None => "<missing>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::pubkey::Pubkey;
+    use anchor_client::solana_sdk::system_instruction;
+
+    #[test]
+    fn describes_token_transfer() {
+        let source = Pubkey::new_unique();
+        let target = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let ix = spl_token::instruction::transfer(
+            &spl_token::id(),
+            &source,
+            &target,
+            &authority,
+            &[],
+            42,
+        )
+        .unwrap();
+        let description = describe_instruction(&ix);
+        assert!(description.starts_with("spl-token transfer 42"));
+        assert!(description.contains(&source.to_string()));
+        assert!(description.contains(&target.to_string()));
+    }
+
+    #[test]
+    fn describes_token_transfer_checked() {
+        let source = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let target = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let ix = spl_token::instruction::transfer_checked(
+            &spl_token::id(),
+            &source,
+            &mint,
+            &target,
+            &authority,
+            &[],
+            1_000_000_001,
+            9,
+        )
+        .unwrap();
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "spl-token transfer 1.000000001 of mint {} from {} to {}, authority {}",
+                mint, source, target, authority
+            )
+        );
+    }
+
+    #[test]
+    fn describes_token_set_authority() {
+        let account = Pubkey::new_unique();
+        let current = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let ix = spl_token::instruction::set_authority(
+            &spl_token::id(),
+            &account,
+            Some(&new_authority),
+            spl_token::instruction::AuthorityType::AccountOwner,
+            &current,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "spl-token set AccountOwner authority of {} from {} to {}",
+                account, current, new_authority
+            )
+        );
+    }
+
+    #[test]
+    fn describes_token_close_account() {
+        let account = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let ix = spl_token::instruction::close_account(
+            &spl_token::id(),
+            &account,
+            &destination,
+            &owner,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "spl-token close account {}, sending lamports to {}, authority {}",
+                account, destination, owner
+            )
+        );
+    }
+
+    #[test]
+    fn describes_token_mint_to() {
+        let mint = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let ix = spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &mint,
+            &account,
+            &authority,
+            &[],
+            42,
+        )
+        .unwrap();
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "spl-token mint 42 (raw amount) of mint {} to {}, authority {}",
+                mint, account, authority
+            )
+        );
+    }
+
+    #[test]
+    fn describes_token_burn() {
+        let account = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let ix = spl_token::instruction::burn(
+            &spl_token::id(),
+            &account,
+            &mint,
+            &authority,
+            &[],
+            42,
+        )
+        .unwrap();
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "spl-token burn 42 (raw amount) of mint {} from {}, authority {}",
+                mint, account, authority
+            )
+        );
+    }
+
+    #[test]
+    fn describes_system_transfer() {
+        let from = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let ix = system_instruction::transfer(&from, &to, 1_500_000_001);
+        assert_eq!(
+            describe_instruction(&ix),
+            format!("system transfer 1.500000001 SOL from {} to {}", from, to)
+        );
+    }
+
+    #[test]
+    fn describes_program_upgrade() {
+        let program = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let spill = Pubkey::new_unique();
+        let ix = bpf_loader_upgradeable::upgrade(&program, &buffer, &authority, &spill);
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "bpf_loader_upgradeable upgrade program {} from buffer {}, spill to {}, authority {}",
+                program, buffer, spill, authority
+            )
+        );
+    }
+
+    #[test]
+    fn describes_loader_set_authority() {
+        let program = Pubkey::new_unique();
+        let current = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let (programdata, _) =
+            Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id());
+        let ix =
+            bpf_loader_upgradeable::set_upgrade_authority(&program, &current, Some(&new_authority));
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "bpf_loader_upgradeable set authority of {} (buffer or program data) from {} to {}",
+                programdata, current, new_authority
+            )
+        );
+    }
+
+    #[test]
+    fn describes_loader_set_authority_to_immutable() {
+        let program = Pubkey::new_unique();
+        let current = Pubkey::new_unique();
+        let (programdata, _) =
+            Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id());
+        let ix = bpf_loader_upgradeable::set_upgrade_authority(&program, &current, None);
+        assert_eq!(
+            describe_instruction(&ix),
+            format!(
+                "bpf_loader_upgradeable set authority of {} (buffer or program data) from {} to none (immutable)",
+                programdata, current
+            )
+        );
+    }
+
+    #[test]
+    fn falls_back_to_raw_dump() {
+        let ix = Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![],
+            data: vec![0xde, 0xad, 0xbe, 0xef],
+        };
+        let description = describe_instruction(&ix);
+        assert!(description.contains("deadbeef"));
+        assert!(description.contains("3q2+7w=="));
+    }
+}
@@ -0,0 +1,214 @@
+//! parsing of instructions produced by other tooling, so that they can be
+//! proposed to the multisig
+
+use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction};
+use anchor_client::solana_sdk::message::Message;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::transaction::Transaction;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// the shape of the serialized instruction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InstructionFormat {
+    /// a bincode serialized `Instruction`
+    Bincode,
+    /// a web3.js style `{programId, keys, data}` json object
+    Json,
+    /// a bincode serialized legacy `Message`
+    Message,
+    /// a bincode serialized legacy `Transaction`
+    Transaction,
+}
+
+/// the text encoding of binary formats
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    Base64,
+    Base58,
+}
+
+impl FromStr for InstructionFormat {
+    type Err = anyhow::Error;
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "bincode" => Ok(InstructionFormat::Bincode),
+            "json" => Ok(InstructionFormat::Json),
+            "message" => Ok(InstructionFormat::Message),
+            "transaction" => Ok(InstructionFormat::Transaction),
+            _ => Err(anyhow!("unsupported instruction format {}", format)),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = anyhow::Error;
+    fn from_str(encoding: &str) -> Result<Self> {
+        match encoding {
+            "base64" => Ok(Encoding::Base64),
+            "base58" => Ok(Encoding::Base58),
+            _ => Err(anyhow!("unsupported encoding {}", encoding)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonInstruction {
+    program_id: String,
+    keys: Vec<JsonAccountMeta>,
+    data: JsonData,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+/// web3.js serializes instruction data as a node `Buffer`, but plain byte
+/// arrays and base64 strings are accepted too
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonData {
+    Bytes(Vec<u8>),
+    Buffer { data: Vec<u8> },
+    Base64(String),
+}
+
+/// parses a single instruction from the input. for messages and transactions
+/// the instruction at `index` is returned
+pub fn parse_instruction(
+    input: &str,
+    format: InstructionFormat,
+    encoding: Encoding,
+    index: usize,
+) -> Result<Instruction> {
+    let input = input.trim();
+    if format == InstructionFormat::Json {
+        return parse_json_instruction(input);
+    }
+    let bytes = match encoding {
+        Encoding::Base64 => base64::decode(input)?,
+        Encoding::Base58 => bs58::decode(input).into_vec()?,
+    };
+    match format {
+        InstructionFormat::Bincode => Ok(bincode::deserialize(&bytes)?),
+        InstructionFormat::Message => {
+            let message: Message = bincode::deserialize(&bytes)?;
+            message_instruction(&message, index)
+        }
+        InstructionFormat::Transaction => {
+            let transaction: Transaction = bincode::deserialize(&bytes)?;
+            message_instruction(&transaction.message, index)
+        }
+        InstructionFormat::Json => unreachable!(),
+    }
+}
+
+fn parse_json_instruction(input: &str) -> Result<Instruction> {
+    let json: JsonInstruction = serde_json::from_str(input)?;
+    let accounts = json
+        .keys
+        .iter()
+        .map(|key| {
+            Ok(AccountMeta {
+                pubkey: Pubkey::from_str(&key.pubkey)?,
+                is_signer: key.is_signer,
+                is_writable: key.is_writable,
+            })
+        })
+        .collect::<Result<Vec<AccountMeta>>>()?;
+    let data = match json.data {
+        JsonData::Bytes(data) => data,
+        JsonData::Buffer { data } => data,
+        JsonData::Base64(data) => base64::decode(data)?,
+    };
+    Ok(Instruction {
+        program_id: Pubkey::from_str(&json.program_id)?,
+        accounts,
+        data,
+    })
+}
+
+/// decompiles the instruction at `index`, restoring the signer and writable
+/// flags from the message header
+fn message_instruction(message: &Message, index: usize) -> Result<Instruction> {
+    let compiled = message.instructions.get(index).ok_or_else(|| {
+        anyhow!(
+            "instruction index {} out of range, message has {} instructions",
+            index,
+            message.instructions.len()
+        )
+    })?;
+    let key = |idx: u8| {
+        message
+            .account_keys
+            .get(idx as usize)
+            .cloned()
+            .ok_or_else(|| anyhow!("account index {} out of range", idx))
+    };
+    let accounts = compiled
+        .accounts
+        .iter()
+        .map(|idx| {
+            Ok(AccountMeta {
+                pubkey: key(*idx)?,
+                is_signer: message.is_signer(*idx as usize),
+                is_writable: message.is_writable(*idx as usize),
+            })
+        })
+        .collect::<Result<Vec<AccountMeta>>>()?;
+    Ok(Instruction {
+        program_id: key(compiled.program_id_index)?,
+        accounts,
+        data: compiled.data.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::system_instruction;
+
+    #[test]
+    fn parses_web3_json() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let input = format!(
+            r#"{{"programId":"{}","keys":[{{"pubkey":"{}","isSigner":false,"isWritable":true}}],"data":{{"type":"Buffer","data":[1,2,3]}}}}"#,
+            program_id, key
+        );
+        let ix = parse_instruction(&input, InstructionFormat::Json, Encoding::Base64, 0).unwrap();
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(ix.accounts, vec![AccountMeta::new(key, false)]);
+        assert_eq!(ix.data, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn parses_base58_bincode() {
+        let ix = system_instruction::transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 42);
+        let input = bs58::encode(bincode::serialize(&ix).unwrap()).into_string();
+        let parsed =
+            parse_instruction(&input, InstructionFormat::Bincode, Encoding::Base58, 0).unwrap();
+        assert_eq!(parsed, ix);
+    }
+
+    #[test]
+    fn picks_instruction_from_message() {
+        let payer = Pubkey::new_unique();
+        let first = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+        let second = system_instruction::transfer(&payer, &Pubkey::new_unique(), 2);
+        let message = Message::new(&[first, second.clone()], Some(&payer));
+        let input = base64::encode(bincode::serialize(&message).unwrap());
+        let parsed =
+            parse_instruction(&input, InstructionFormat::Message, Encoding::Base64, 1).unwrap();
+        assert_eq!(parsed, second);
+        assert!(
+            parse_instruction(&input, InstructionFormat::Message, Encoding::Base64, 2).is_err()
+        );
+    }
+}
@@ -1,5 +1,8 @@
+pub mod amount;
+pub mod decode;
+pub mod encoding;
 pub mod request_builder;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
 use crate::request_builder::RequestBuilder;
 use anchor_client::anchor_lang;
@@ -26,14 +29,27 @@ use anchor_client::Program;
 use anchor_client::RequestNamespace;
 use serum_multisig::TransactionAccount;
 use std::sync::Arc;
+use thiserror::Error;
+
+/// errors returned when an instruction cannot be proposed to the multisig
+#[derive(Debug, Error, PartialEq)]
+pub enum ProposalError {
+    #[error("instruction requires signer {signer} but only the multisig pda {pda} can sign")]
+    UnexpectedSigner { signer: Pubkey, pda: Pubkey },
+}
+
 impl<'a> RequestBuilder<'a> {
     pub fn propose_blob_ix(
         &self,
         multisig: Pubkey,
         data: &str
     ) -> Result<Pubkey> {
-        let ix_data = base64::decode(data)?;
-        let ix: Instruction = bincode::deserialize(&ix_data[..])?;
+        let ix = encoding::parse_instruction(
+            data,
+            encoding::InstructionFormat::Bincode,
+            encoding::Encoding::Base64,
+            0,
+        )?;
         self.propose_solana_instruction(&multisig, ix)
     }
     pub fn propose_transfer_tokens(
@@ -43,15 +59,42 @@ impl<'a> RequestBuilder<'a> {
         source: Pubkey,
         target: Pubkey,
         amount: u64,
+        create_target_owner: Option<Pubkey>,
     ) -> Result<Pubkey> {
-        let ix = spl_token::instruction::transfer(
+        // transfer_checked makes the token program verify the mint and
+        // decimals again when the proposal is executed
+        let (mint, decimals) = self.token_account_mint(&source)?;
+        let ix = spl_token::instruction::transfer_checked(
             &spl_token::id(),
             &source,
+            &mint,
             &target,
             &pda,
             &vec![&pda],
             amount,
+            decimals,
         )?;
+        // the target ata is created by the proposer in the same transaction
+        // that files the proposal
+        let builder = create_target_owner.map(|owner| {
+            self.request().instruction(
+                spl_associated_token_account::create_associated_token_account(
+                    &self.payer.pubkey(),
+                    &owner,
+                    &mint,
+                ),
+            )
+        });
+        self.propose_solana_instruction_with_builder(builder, &multisig, ix)
+    }
+    pub fn propose_transfer_sol(
+        &self,
+        multisig: Pubkey,
+        pda: Pubkey,
+        target: Pubkey,
+        lamports: u64,
+    ) -> Result<Pubkey> {
+        let ix = system_instruction::transfer(&pda, &target, lamports);
         self.propose_solana_instruction(&multisig, ix)
     }
     pub fn propose_solana_instruction(
@@ -59,6 +102,25 @@ impl<'a> RequestBuilder<'a> {
         multisig: &Pubkey,
         instruction: Instruction,
     ) -> Result<Pubkey> {
+        self.propose_solana_instruction_with_builder(None, multisig, instruction)
+    }
+    /// proposes the instruction, sending the proposal with any instructions
+    /// already queued on the given builder
+    pub fn propose_solana_instruction_with_builder(
+        &self,
+        builder: Option<RequestBuilder>,
+        multisig: &Pubkey,
+        instruction: Instruction,
+    ) -> Result<Pubkey> {
+        check_signers(&instruction, &self.multisig_signer(multisig)?)?;
+        // any instructions queued alongside the proposal, such as ata
+        // creation, have to run first for the simulation to be meaningful
+        let mut simulated = match &builder {
+            Some(builder) => builder.instructions.clone(),
+            None => vec![],
+        };
+        simulated.push(instruction.clone());
+        self.simulate_proposal(&simulated)?;
         let accounts = instruction
             .accounts
             .iter()
@@ -69,13 +131,136 @@ impl<'a> RequestBuilder<'a> {
             })
             .collect::<Vec<TransactionAccount>>();
         self.create_transaction(
-            None,
+            builder,
             *multisig,
             instruction.program_id,
             accounts,
             instruction.data,
         )
     }
+    /// simulates the instructions of a proposal before it is filed, with the
+    /// multisig pda marked as a signer the way it is when executed. a failed
+    /// simulation is an error unless the builder was forced
+    pub fn simulate_proposal(&self, ixs: &[Instruction]) -> Result<()> {
+        let simulation = self.simulate_instructions(ixs)?;
+        let logs = simulation.logs.unwrap_or_default();
+        println!("simulation logs:");
+        for log in logs.iter() {
+            println!("\t{}", log);
+        }
+        if let Some(units) = compute_units(&logs) {
+            println!("simulation consumed {} compute units", units);
+        }
+        match simulation.err {
+            None => Ok(()),
+            Some(err) if self.force => {
+                println!("simulation failed with {:?}, proposing anyway", err);
+                Ok(())
+            }
+            Some(err) => Err(anyhow!(
+                "simulation failed with {:?}, pass --force to propose anyway",
+                err
+            )),
+        }
+    }
+    /// proposes replacing the owners of the multisig, which bumps the
+    /// owner_set_seqno and invalidates every pending transaction
+    pub fn propose_set_owners(
+        &self,
+        multisig: Pubkey,
+        pda: Pubkey,
+        owners: Vec<Pubkey>,
+    ) -> Result<Pubkey> {
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: serum_multisig::accounts::Auth {
+                multisig,
+                multisig_signer: pda,
+            }
+            .to_account_metas(None),
+            data: serum_multisig::instruction::SetOwners { owners }.data(),
+        };
+        self.propose_solana_instruction(&multisig, ix)
+    }
+    /// proposes changing the approval threshold of the multisig
+    pub fn propose_change_threshold(
+        &self,
+        multisig: Pubkey,
+        pda: Pubkey,
+        threshold: u64,
+    ) -> Result<Pubkey> {
+        let multisig_account = self.load_multisig(&multisig)?;
+        validate_threshold(threshold, multisig_account.owners.len())?;
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: serum_multisig::accounts::Auth {
+                multisig,
+                multisig_signer: pda,
+            }
+            .to_account_metas(None),
+            data: serum_multisig::instruction::ChangeThreshold { threshold }.data(),
+        };
+        self.propose_solana_instruction(&multisig, ix)
+    }
+    /// proposes replacing the owners and the approval threshold of the
+    /// multisig in a single instruction
+    pub fn propose_set_owners_and_change_threshold(
+        &self,
+        multisig: Pubkey,
+        pda: Pubkey,
+        owners: Vec<Pubkey>,
+        threshold: u64,
+    ) -> Result<Pubkey> {
+        validate_threshold(threshold, owners.len())?;
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: serum_multisig::accounts::Auth {
+                multisig,
+                multisig_signer: pda,
+            }
+            .to_account_metas(None),
+            data: serum_multisig::instruction::SetOwnersAndChangeThreshold { owners, threshold }
+                .data(),
+        };
+        self.propose_solana_instruction(&multisig, ix)
+    }
+    /// proposes upgrading the program with the contents of the buffer, the
+    /// buffer authority must already be the multisig pda
+    pub fn propose_program_upgrade(
+        &self,
+        multisig: Pubkey,
+        program: Pubkey,
+        buffer: Pubkey,
+        spill: Pubkey,
+    ) -> Result<Pubkey> {
+        let pda = self.multisig_signer(&multisig)?;
+        let authority_address = self.buffer_authority(&buffer)?;
+        if authority_address != Some(pda) {
+            return Err(anyhow!(
+                "buffer authority is {:?}, expected multisig pda {}",
+                authority_address,
+                pda
+            ));
+        }
+        self.propose_solana_instruction(
+            &multisig,
+            bpf_loader_upgradeable::upgrade(&program, &buffer, &pda, &spill),
+        )
+    }
+    /// proposes changing the upgrade authority of a program currently owned by
+    /// the multisig pda, passing `None` makes the program immutable
+    pub fn propose_set_upgrade_authority(
+        &self,
+        multisig: Pubkey,
+        program: Pubkey,
+        new_authority: Option<Pubkey>,
+    ) -> Result<Pubkey> {
+        let pda = self.multisig_signer(&multisig)?;
+        self.propose_solana_instruction(
+            &multisig,
+            bpf_loader_upgradeable::set_upgrade_authority(&program, &pda, new_authority.as_ref()),
+        )
+    }
     pub fn propose_change_auth(
         &self,
         multisig: &Pubkey,
@@ -94,10 +279,88 @@ impl<'a> RequestBuilder<'a> {
     }
 }
 
+/// ensures a threshold can be met by the given number of owners, so that
+/// a multisig can never be proposed into a state that locks itself out
+pub fn validate_threshold(threshold: u64, owner_count: usize) -> Result<()> {
+    if threshold == 0 {
+        return Err(anyhow!("threshold must be greater than zero"));
+    }
+    if threshold > owner_count as u64 {
+        return Err(anyhow!(
+            "threshold {} exceeds the number of owners {}",
+            threshold,
+            owner_count
+        ));
+    }
+    Ok(())
+}
+
+/// ensures the multisig pda is the only signer the instruction requires, as
+/// no other signature can be provided when the proposal is executed
+pub fn check_signers(instruction: &Instruction, pda: &Pubkey) -> Result<(), ProposalError> {
+    match instruction
+        .accounts
+        .iter()
+        .find(|meta| meta.is_signer && meta.pubkey != *pda)
+    {
+        Some(meta) => Err(ProposalError::UnexpectedSigner {
+            signer: meta.pubkey,
+            pda: *pda,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// returns the compute units consumed by the last top level instruction,
+/// which is the proposed instruction when simulating a proposal
+pub fn compute_units(logs: &[String]) -> Option<u64> {
+    logs.iter().rev().find_map(|log| {
+        let consumed = log.split(" consumed ").nth(1)?;
+        consumed.split(' ').next()?.parse().ok()
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn validates_threshold() {
+        assert!(validate_threshold(0, 3).is_err());
+        assert!(validate_threshold(4, 3).is_err());
+        assert!(validate_threshold(1, 0).is_err());
+        assert!(validate_threshold(1, 3).is_ok());
+        assert!(validate_threshold(3, 3).is_ok());
+    }
+
+    #[test]
+    fn rejects_signers_other_than_pda() {
+        let pda = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let ix = system_instruction::transfer(&pda, &other, 1);
+        assert_eq!(check_signers(&ix, &pda), Ok(()));
+        let ix = system_instruction::transfer(&other, &pda, 1);
+        assert_eq!(
+            check_signers(&ix, &pda),
+            Err(ProposalError::UnexpectedSigner { signer: other, pda })
+        );
+    }
+
+    #[test]
+    fn parses_compute_units() {
+        let logs = vec![
+            "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+            "Program 11111111111111111111111111111111 consumed 150 of 200000 compute units".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [1]".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 2941 of 199850 compute units".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+        ];
+        assert_eq!(compute_units(&logs), Some(2941));
+        assert_eq!(compute_units(&[]), None);
+    }
 }
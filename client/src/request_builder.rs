@@ -1,15 +1,26 @@
 #![allow(dead_code, unused_variables, unused_imports)]
 
 use anchor_client::anchor_lang;
+use anchor_client::anchor_lang::AccountDeserialize;
 use anchor_client::anchor_lang::InstructionData;
 use anchor_client::anchor_lang::ToAccountMetas;
 use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcAccountInfoConfig;
+use anchor_client::solana_client::rpc_config::RpcProgramAccountsConfig;
 use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use anchor_client::solana_client::rpc_config::RpcSimulateTransactionAccountsConfig;
+use anchor_client::solana_client::rpc_config::RpcSimulateTransactionConfig;
+use anchor_client::solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use anchor_client::solana_client::rpc_request::{TokenAccountsFilter, MAX_MULTIPLE_ACCOUNTS};
+use anchor_client::solana_client::rpc_response::RpcSimulateTransactionResult;
+use anchor_client::solana_sdk::account::Account;
 use anchor_client::solana_sdk::bpf_loader_upgradeable;
+use anchor_client::solana_sdk::bpf_loader_upgradeable::UpgradeableLoaderState;
 use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
 use anchor_client::solana_sdk::commitment_config::CommitmentLevel;
 use anchor_client::solana_sdk::instruction::AccountMeta;
 use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::Message;
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::solana_sdk::rent;
 use anchor_client::solana_sdk::signature::Signature;
@@ -22,9 +33,29 @@ use anchor_client::Client;
 use anchor_client::ClientError;
 use anchor_client::Program;
 use anchor_client::RequestNamespace;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rand::rngs::OsRng;
+use serum_multisig::Multisig;
+use spl_token::solana_program::program_pack::Pack;
+use std::collections::HashMap;
+use std::str::FromStr;
 use serum_multisig::TransactionAccount;
+use solana_account_decoder::UiAccountEncoding;
+
+/// the number of program bytes written to a buffer per transaction, leaving
+/// room for the signature, account keys and write instruction header
+pub const BUFFER_WRITE_CHUNK_SIZE: usize = 900;
+/// the number of approve instructions packed into a single transaction
+pub const APPROVALS_PER_TRANSACTION: usize = 10;
+
+/// a token account along with its balance and mint decimals
+pub struct TokenBalance {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
 /// `RequestBuilder` provides a builder interface to create and send
 /// transactions to a cluster.
 pub struct RequestBuilder<'a> {
@@ -40,6 +71,8 @@ pub struct RequestBuilder<'a> {
     // True if the user is sending a state instruction.
     pub namespace: RequestNamespace,
     pub rpc: RpcClient,
+    // True if proposals should be filed even when their simulation fails.
+    pub force: bool,
 }
 
 impl<'a> RequestBuilder<'a> {
@@ -61,6 +94,7 @@ impl<'a> RequestBuilder<'a> {
             signers: Vec::new(),
             namespace,
             rpc: RpcClient::new(cluster.to_string()),
+            force: false,
         }
     }
 
@@ -108,6 +142,11 @@ impl<'a> RequestBuilder<'a> {
         self
     }
 
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
     pub fn signer(mut self, signer: &'a dyn Signer) -> Self {
         self.signers.push(signer);
         self
@@ -238,8 +277,8 @@ impl<'a> RequestBuilder<'a> {
         data: Vec<u8>,
     ) -> Result<Pubkey> {
         let tx_acct = Keypair::generate(&mut OsRng);
-        let mut builder = self
-            .request()
+        let mut builder = builder
+            .unwrap_or_else(|| self.request())
             .instruction(system_instruction::create_account(
                 &&self.payer.pubkey(),
                 &tx_acct.pubkey(),
@@ -259,6 +298,363 @@ impl<'a> RequestBuilder<'a> {
         println!("sent tx {}", sig);
         Ok(tx_acct.pubkey())
     }
+    /// approves the given multisig transaction, failing early if the payer
+    /// is not one of the on-chain owners of the multisig
+    pub fn approve(&self, multisig: Pubkey, transaction: Pubkey) -> Result<Signature> {
+        let multisig_account = self.load_multisig(&multisig)?;
+        if !multisig_account.owners.contains(&self.payer.pubkey()) {
+            return Err(anyhow!(
+                "signer {} is not an owner of multisig {}",
+                self.payer.pubkey(),
+                multisig
+            ));
+        }
+        let sig = self
+            .request()
+            .accounts(serum_multisig::accounts::Approve {
+                multisig,
+                transaction,
+                owner: self.payer.pubkey(),
+            })
+            .args(serum_multisig::instruction::Approve)
+            .send(true)?;
+        Ok(sig)
+    }
+    /// approves several multisig transactions, packing the approve instructions
+    /// into as few transactions as possible
+    pub fn approve_many(&self, multisig: Pubkey, transactions: &[Pubkey]) -> Result<Vec<Signature>> {
+        let multisig_account = self.load_multisig(&multisig)?;
+        if !multisig_account.owners.contains(&self.payer.pubkey()) {
+            return Err(anyhow!(
+                "signer {} is not an owner of multisig {}",
+                self.payer.pubkey(),
+                multisig
+            ));
+        }
+        let mut sigs = Vec::new();
+        for chunk in transactions.chunks(APPROVALS_PER_TRANSACTION) {
+            let mut builder = self.request();
+            for transaction in chunk {
+                builder = builder.instruction(Instruction {
+                    program_id: self.program_id,
+                    accounts: serum_multisig::accounts::Approve {
+                        multisig,
+                        transaction: *transaction,
+                        owner: self.payer.pubkey(),
+                    }
+                    .to_account_metas(None),
+                    data: serum_multisig::instruction::Approve.data(),
+                });
+            }
+            sigs.push(builder.send(true)?);
+        }
+        Ok(sigs)
+    }
+    /// executes a multisig transaction once it has reached the approval threshold
+    pub fn execute_transaction(&self, multisig: Pubkey, transaction: Pubkey) -> Result<Signature> {
+        let ix = self.execute_transaction_instruction(multisig, transaction)?;
+        let sig = self.request().instruction(ix).send(true)?;
+        Ok(sig)
+    }
+    /// simulates executing a multisig transaction without sending it
+    pub fn simulate_execute_transaction(
+        &self,
+        multisig: Pubkey,
+        transaction: Pubkey,
+    ) -> Result<RpcSimulateTransactionResult> {
+        let ix = self.execute_transaction_instruction(multisig, transaction)?;
+        self.simulate_instructions(&[ix])
+    }
+    /// builds the execute instruction for a multisig transaction, refusing
+    /// transactions that are executed, stale or below the approval threshold
+    pub fn execute_transaction_instruction(
+        &self,
+        multisig: Pubkey,
+        transaction: Pubkey,
+    ) -> Result<Instruction> {
+        let multisig_account = self.load_multisig(&multisig)?;
+        let tx_account = self.load_transaction(&transaction)?;
+        if tx_account.did_execute {
+            return Err(anyhow!("transaction {} has already been executed", transaction));
+        }
+        if tx_account.owner_set_seqno != multisig_account.owner_set_seqno {
+            return Err(anyhow!(
+                "transaction {} was created for a previous owner set",
+                transaction
+            ));
+        }
+        let approvals = tx_account.signers.iter().filter(|signed| **signed).count() as u64;
+        if approvals < multisig_account.threshold {
+            return Err(anyhow!(
+                "transaction {} has {} approvals, threshold is {}",
+                transaction,
+                approvals,
+                multisig_account.threshold
+            ));
+        }
+        let multisig_signer = Pubkey::create_program_address(
+            &[multisig.as_ref(), &[multisig_account.nonce]],
+            &self.program_id,
+        )?;
+        let mut accounts = serum_multisig::accounts::ExecuteTransaction {
+            multisig,
+            multisig_signer,
+            transaction,
+        }
+        .to_account_metas(None);
+        // the pda signs through invoke_signed within the program, so it must
+        // not be marked as a signer in the outer transaction
+        accounts.extend(tx_account.accounts.iter().map(|account| AccountMeta {
+            pubkey: account.pubkey,
+            is_signer: account.pubkey != multisig_signer && account.is_signer,
+            is_writable: account.is_writable,
+        }));
+        accounts.push(AccountMeta::new_readonly(tx_account.program_id, false));
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: serum_multisig::instruction::ExecuteTransaction.data(),
+        })
+    }
+    /// simulates the instructions with the payer as fee payer, the transaction
+    /// is left unsigned so hardware wallets are not prompted
+    pub fn simulate_instructions(&self, ixs: &[Instruction]) -> Result<RpcSimulateTransactionResult> {
+        self.simulate_instructions_with_accounts(ixs, &[])
+    }
+    /// simulates the instructions, returning the post simulation state of the
+    /// given accounts alongside the logs
+    pub fn simulate_instructions_with_accounts(
+        &self,
+        ixs: &[Instruction],
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult> {
+        let (blockhash, _) = self.rpc.get_recent_blockhash()?;
+        let mut tx = Transaction::new_unsigned(Message::new(ixs, Some(&self.payer.pubkey())));
+        tx.message.recent_blockhash = blockhash;
+        let accounts = if addresses.is_empty() {
+            None
+        } else {
+            Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: addresses.iter().map(|address| address.to_string()).collect(),
+            })
+        };
+        let res = self.rpc.simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                commitment: Some(self.rpc.commitment()),
+                accounts,
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?;
+        Ok(res.value)
+    }
+    /// creates a buffer account large enough to hold the program, with the
+    /// payer as the buffer authority
+    pub fn create_buffer(&self, buffer: &Keypair, program_len: usize) -> Result<Signature> {
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::buffer_len(
+                program_len,
+            )?)?;
+        let ixs = bpf_loader_upgradeable::create_buffer(
+            &self.payer.pubkey(),
+            &buffer.pubkey(),
+            &self.payer.pubkey(),
+            lamports,
+            program_len,
+        )?;
+        self.send_instructions(&ixs, vec![self.payer, buffer])
+    }
+    /// writes the program into the buffer in chunks, skipping any chunk whose
+    /// contents are already on-chain so that an interrupted write can be resumed
+    pub fn write_buffer(&self, buffer: &Pubkey, program: &[u8]) -> Result<()> {
+        let buffer_data = self.rpc.get_account_data(buffer)?;
+        let data_offset = UpgradeableLoaderState::buffer_data_offset()?;
+        let written = buffer_data.get(data_offset..).unwrap_or_default();
+        let total_chunks = (program.len() + BUFFER_WRITE_CHUNK_SIZE - 1) / BUFFER_WRITE_CHUNK_SIZE;
+        for (idx, chunk) in program.chunks(BUFFER_WRITE_CHUNK_SIZE).enumerate() {
+            let offset = idx * BUFFER_WRITE_CHUNK_SIZE;
+            if written.get(offset..offset + chunk.len()) == Some(chunk) {
+                println!("chunk {}/{} already written", idx + 1, total_chunks);
+                continue;
+            }
+            let ix = bpf_loader_upgradeable::write(
+                buffer,
+                &self.payer.pubkey(),
+                offset as u32,
+                chunk.to_vec(),
+            );
+            self.send_instructions(&[ix], vec![self.payer])?;
+            println!("wrote chunk {}/{}", idx + 1, total_chunks);
+        }
+        Ok(())
+    }
+    /// transfers the buffer authority from the payer to the new authority
+    pub fn set_buffer_authority(&self, buffer: &Pubkey, new_authority: &Pubkey) -> Result<Signature> {
+        let ix = bpf_loader_upgradeable::set_buffer_authority(
+            buffer,
+            &self.payer.pubkey(),
+            new_authority,
+        );
+        self.send_instructions(&[ix], vec![self.payer])
+    }
+    /// transfers the upgrade authority of a program from the payer to the new authority
+    pub fn set_upgrade_authority(&self, program: &Pubkey, new_authority: &Pubkey) -> Result<Signature> {
+        let ix = bpf_loader_upgradeable::set_upgrade_authority(
+            program,
+            &self.payer.pubkey(),
+            Some(new_authority),
+        );
+        self.send_instructions(&[ix], vec![self.payer])
+    }
+    /// returns the upgrade authority of the given program
+    pub fn upgrade_authority(&self, program: &Pubkey) -> Result<Option<Pubkey>> {
+        let (programdata, _) =
+            Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id());
+        let programdata_data = self.rpc.get_account_data(&programdata)?;
+        match bincode::deserialize(&programdata_data[..])? {
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            } => Ok(upgrade_authority_address),
+            _ => Err(anyhow!("{} is not an upgradeable program", program)),
+        }
+    }
+    /// returns the authority of the given buffer account
+    pub fn buffer_authority(&self, buffer: &Pubkey) -> Result<Option<Pubkey>> {
+        let buffer_data = self.rpc.get_account_data(buffer)?;
+        match bincode::deserialize(&buffer_data[..])? {
+            UpgradeableLoaderState::Buffer { authority_address } => Ok(authority_address),
+            _ => Err(anyhow!("{} is not a buffer account", buffer)),
+        }
+    }
+    /// returns the mint of the given token account along with the mint decimals
+    pub fn token_account_mint(&self, token_account: &Pubkey) -> Result<(Pubkey, u8)> {
+        let account_data = self.rpc.get_account_data(token_account)?;
+        let account = spl_token::state::Account::unpack(&account_data)?;
+        let mint_data = self.rpc.get_account_data(&account.mint)?;
+        let mint = spl_token::state::Mint::unpack(&mint_data)?;
+        Ok((account.mint, mint.decimals))
+    }
+    /// resolves a transfer target into a token account for the given mint,
+    /// anything that isn't a token account is treated as a wallet and resolved
+    /// to its ata. returns the token account and whether it exists yet. rpc
+    /// failures are returned rather than mistaken for missing accounts
+    pub fn resolve_token_target(&self, target: &Pubkey, mint: &Pubkey) -> Result<(Pubkey, bool)> {
+        let commitment = self.rpc.commitment();
+        if let Some(account) = self.rpc.get_account_with_commitment(target, commitment)?.value {
+            if account.owner == spl_token::id() {
+                let token_account = spl_token::state::Account::unpack(&account.data)?;
+                if token_account.mint != *mint {
+                    return Err(anyhow!(
+                        "target {} holds mint {}, expected {}",
+                        target,
+                        token_account.mint,
+                        mint
+                    ));
+                }
+                return Ok((*target, true));
+            }
+        }
+        let ata = spl_associated_token_account::get_associated_token_address(target, mint);
+        let exists = self.rpc.get_account_with_commitment(&ata, commitment)?.value.is_some();
+        Ok((ata, exists))
+    }
+    /// returns every spl token account owned by the given owner, fetching the
+    /// accounts and their mints in batches
+    pub fn token_balances(&self, owner: &Pubkey) -> Result<Vec<TokenBalance>> {
+        let addresses = self
+            .rpc
+            .get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(spl_token::id()))?
+            .iter()
+            .map(|keyed_account| Pubkey::from_str(&keyed_account.pubkey))
+            .collect::<Result<Vec<Pubkey>, _>>()?;
+        let mut token_accounts = Vec::with_capacity(addresses.len());
+        for (address, account) in addresses.iter().zip(self.get_multiple_accounts(&addresses)?) {
+            if let Some(account) = account {
+                token_accounts.push((*address, spl_token::state::Account::unpack(&account.data)?));
+            }
+        }
+        let mut mints: Vec<Pubkey> = token_accounts.iter().map(|(_, account)| account.mint).collect();
+        mints.sort();
+        mints.dedup();
+        let mut decimals = HashMap::new();
+        for (mint, account) in mints.iter().zip(self.get_multiple_accounts(&mints)?) {
+            if let Some(account) = account {
+                decimals.insert(*mint, spl_token::state::Mint::unpack(&account.data)?.decimals);
+            }
+        }
+        Ok(token_accounts
+            .into_iter()
+            .map(|(address, account)| TokenBalance {
+                address,
+                mint: account.mint,
+                amount: account.amount,
+                decimals: decimals.get(&account.mint).cloned().unwrap_or_default(),
+            })
+            .collect())
+    }
+    /// fetches the given accounts, splitting the request to stay within the
+    /// rpc limit for a single getMultipleAccounts call
+    pub fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            accounts.append(&mut self.rpc.get_multiple_accounts(chunk)?);
+        }
+        Ok(accounts)
+    }
+    /// derives the multisig pda from the nonce stored on-chain
+    pub fn multisig_signer(&self, multisig: &Pubkey) -> Result<Pubkey> {
+        let multisig_account = self.load_multisig(multisig)?;
+        Ok(Pubkey::create_program_address(
+            &[multisig.as_ref(), &[multisig_account.nonce]],
+            &self.program_id,
+        )?)
+    }
+    /// fetches and deserializes the on-chain multisig account
+    pub fn load_multisig(&self, multisig: &Pubkey) -> Result<Multisig> {
+        let data = self.rpc.get_account_data(multisig)?;
+        Ok(Multisig::try_deserialize(&mut data.as_slice())?)
+    }
+    /// fetches and deserializes an on-chain multisig transaction account
+    pub fn load_transaction(&self, transaction: &Pubkey) -> Result<serum_multisig::Transaction> {
+        let data = self.rpc.get_account_data(transaction)?;
+        Ok(serum_multisig::Transaction::try_deserialize(
+            &mut data.as_slice(),
+        )?)
+    }
+    /// returns every transaction account proposed to the given multisig
+    pub fn load_transactions(
+        &self,
+        multisig: &Pubkey,
+    ) -> Result<Vec<(Pubkey, serum_multisig::Transaction)>> {
+        // the multisig key is the first field after the 8 byte discriminator
+        let accounts = self.rpc.get_program_accounts_with_config(
+            &self.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
+                    offset: 8,
+                    bytes: MemcmpEncodedBytes::Binary(multisig.to_string()),
+                    encoding: None,
+                })]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(address, account)| {
+                serum_multisig::Transaction::try_deserialize(&mut account.data.as_slice())
+                    .ok()
+                    .map(|transaction| (address, transaction))
+            })
+            .collect())
+    }
     pub fn request(&self) -> RequestBuilder {
         RequestBuilder::from(
             self.program_id,
@@ -269,4 +665,14 @@ impl<'a> RequestBuilder<'a> {
         )
     }
     fn rpc_snd() {}
+    fn send_instructions(&self, ixs: &[Instruction], signers: Vec<&dyn Signer>) -> Result<Signature> {
+        let (blockhash, _) = self.rpc.get_recent_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &signers,
+            blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction_with_spinner(&tx)?)
+    }
 }
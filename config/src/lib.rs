@@ -40,6 +40,9 @@ pub struct MultiSigAccount {
     pub owners: Vec<String>,
     // (name, address)
     pub token_accounts: Vec<(String, String)>,
+    // (name, program id) of programs whose upgrade authority is the pda
+    #[serde(default)]
+    pub programs: Vec<(String, String)>,
 }
 
 impl MultiSigAccount {
@@ -49,6 +52,24 @@ impl MultiSigAccount {
     pub fn pda(&self) -> Pubkey {
         Pubkey::from_str(self.pda.as_str()).unwrap()
     }
+    /// returns the address of a token account by searching for its name
+    pub fn token_account_by_name(&self, name: &str) -> Option<Pubkey> {
+        for (token_name, address) in self.token_accounts.iter() {
+            if token_name.eq(name) {
+                return Pubkey::from_str(address.as_str()).ok();
+            }
+        }
+        None
+    }
+    /// returns the program id of an adopted program by searching for its name
+    pub fn program_by_name(&self, name: &str) -> Option<Pubkey> {
+        for (program_name, program_id) in self.programs.iter() {
+            if program_name.eq(name) {
+                return Pubkey::from_str(program_id.as_str()).ok();
+            }
+        }
+        None
+    }
 }
 
 impl MultiSignature {